@@ -0,0 +1,100 @@
+use bezier::geometry::*;
+use bezier::BENCH_STROKES_NUM;
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use glam::vec2;
+
+/// xorshift, the same curves on every run so timings are comparable
+fn random_curves(num: usize) -> Vec<QuadCurve> {
+    let mut state = 0x2545_f491_u32;
+    let mut random = move || {
+        state ^= state << 13;
+        state ^= state >> 17;
+        state ^= state << 5;
+        (state % 1000) as f32
+    };
+    (0..num)
+        .map(|_| {
+            QuadCurve::new(
+                vec2(random(), random()),
+                vec2(random(), random()),
+                vec2(random(), random()),
+            )
+        })
+        .collect()
+}
+
+fn tessellation(c: &mut Criterion) {
+    let curves = random_curves(BENCH_STROKES_NUM);
+    let mut path = BezierPath::default();
+    path.curves = curves.clone();
+    c.bench_function("path vertices", |b| {
+        b.iter(|| black_box(&path).vertices(black_box(10.)))
+    });
+    let mut dashed = BezierPath::default();
+    dashed.curves = curves.clone();
+    dashed.dash_pattern = Some(vec![10., 5.]);
+    c.bench_function("dashed path vertices", |b| {
+        b.iter(|| black_box(&dashed).vertices(black_box(10.)))
+    });
+    // bytes uploaded per frame: vertices and indices per quad corner for `Strokes::new`,
+    // one instance per curve for `Strokes::new_instanced` over a quad shared by all curves
+    let (vertices, indices) = path.vertices(10.);
+    println!(
+        "upload of {} curves: {} bytes of vertices and indices, {} bytes of instances",
+        path.curves.len(),
+        std::mem::size_of_val(vertices.as_slice()) + std::mem::size_of_val(indices.as_slice()),
+        path.curves.len() * std::mem::size_of::<CurveInstance>(),
+    );
+    c.bench_function("path instances", |b| {
+        b.iter(|| {
+            let (vertices, _) = black_box(&path).vertices(black_box(10.));
+            vertices
+                .chunks(4)
+                .map(CurveInstance::from_quad)
+                .collect::<Vec<_>>()
+        })
+    });
+    // a curve's vertices are the corners of its bounding quad
+    c.bench_function("curve vertices", |b| {
+        b.iter(|| {
+            for curve in curves.iter() {
+                black_box(curve.vertices(black_box(10.)));
+            }
+        })
+    });
+    c.bench_function("oriented_bounding_box", |b| {
+        b.iter(|| {
+            for curve in curves.iter() {
+                black_box(curve.oriented_bounding_box(black_box(10.)));
+            }
+        })
+    });
+}
+
+/// a long finished drawing under a short active path, `Strokes::update_buffers` rebuilds
+/// both while `Strokes::update_active_buffers` rebuilds the active path only
+#[cfg(feature = "render")]
+fn rebuild(c: &mut Criterion) {
+    use bezier::bstroke::Canvas;
+
+    let mut drawing = BezierPath::default();
+    drawing.curves = random_curves(5000);
+    let mut canvas = Canvas::new(5000 + BENCH_STROKES_NUM, false);
+    canvas.push_path(drawing, 10.);
+    canvas.path_mut().curves = random_curves(BENCH_STROKES_NUM);
+    let mode = PrimitiveMode::default();
+    let mut rebuild = c.benchmark_group("rebuild");
+    rebuild.bench_function("full", |b| {
+        b.iter(|| canvas.update(1., black_box(DEFAULT_AA_WIDTH), mode))
+    });
+    rebuild.bench_function("active path", |b| {
+        b.iter(|| canvas.update_active(1., black_box(DEFAULT_AA_WIDTH), mode))
+    });
+    rebuild.finish();
+}
+
+#[cfg(feature = "render")]
+criterion_group!(benches, tessellation, rebuild);
+#[cfg(not(feature = "render"))]
+criterion_group!(benches, tessellation);
+criterion_main!(benches);
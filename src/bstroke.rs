@@ -1,38 +1,335 @@
-use glam::{vec2, Vec2};
+use glam::{vec2, Mat3, Vec2, Vec4};
 use miniquad::*;
-use std::time::Duration;
 
-use crate::Timer;
+use crate::{frames_per_second, Timer};
 
 use crate::geometry::*;
+pub use crate::shape::Shape;
 
-pub const BENCH_STROKES_NUM: usize = 100;
+/// thinner strokes fall under a pixel and vanish in antialiasing
+pub const MIN_PATH_WIDTH: f32 = 0.5;
+/// narrower edges are aliased anyway, zero would divide by zero in the shader
+pub const MIN_AA_WIDTH: f32 = 0.01;
+/// distance in screen pixels from a curve point at which a click grabs it for dragging
+pub const HANDLE_RADIUS: f32 = 6.;
+/// distance in screen pixels from the cursor within which right button drags erase curves
+pub const ERASER_RADIUS: f32 = 10.;
+/// smallest device pixels per screen pixel, zero would make every stroke vanish
+pub const MIN_DPI_SCALE: f32 = 0.1;
+
+/// space in screen pixels left around the drawing by `Strokes::fit_view`
+pub const FIT_MARGIN: f32 = 20.;
+
+/// zoom factor applied per mouse wheel step
+pub const ZOOM_STEP: f32 = 1.1;
+pub const MIN_ZOOM: f32 = 0.05;
+pub const MAX_ZOOM: f32 = 50.;
+
+/// mapping of world coordinates the path is stored in to screen pixels,
+/// `screen = world * scale + offset`
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct View {
+    pub scale: f32,
+    pub offset: Vec2,
+}
+
+impl Default for View {
+    fn default() -> View {
+        View {
+            scale: 1.,
+            offset: Vec2::zero(),
+        }
+    }
+}
+
+impl View {
+    pub fn to_screen(&self, world: Vec2) -> Vec2 {
+        world * self.scale + self.offset
+    }
+
+    pub fn to_world(&self, screen: Vec2) -> Vec2 {
+        (screen - self.offset) / self.scale
+    }
+
+    pub fn matrix(&self) -> Mat3 {
+        Mat3::from_scale_angle_translation(Vec2::splat(self.scale), 0., self.offset)
+    }
+
+    /// multiplies the scale by `factor` keeping the world point under `screen` in place.
+    /// Scale is clamped to `MIN_ZOOM..=MAX_ZOOM`
+    pub fn zoom_at(&mut self, screen: Vec2, factor: f32) {
+        let world = self.to_world(screen);
+        self.scale = (self.scale * factor).clamp(MIN_ZOOM, MAX_ZOOM);
+        self.offset = screen - world * self.scale;
+    }
+
+    pub fn pan(&mut self, delta: Vec2) {
+        self.offset += delta;
+    }
+
+    /// view with the box `mi`..`ma` centered on a `screen` sized rectangle at the origin,
+    /// zoomed to fit with `margin` around like `fit_box` within `MIN_ZOOM..=MAX_ZOOM`
+    pub fn fitting(mi: Vec2, ma: Vec2, screen: Vec2, margin: f32) -> View {
+        let (scale, _) = fit_box(mi, ma, screen, margin);
+        let scale = scale.clamp(MIN_ZOOM, MAX_ZOOM);
+        View {
+            scale,
+            offset: screen / 2. - (mi + ma) / 2. * scale,
+        }
+    }
+}
+
+/// exponential moving average of input points, `factor` is the weight of the history:
+/// 0 passes points through, values toward 1 smooth more and lag behind the input
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct InputFilter {
+    pub factor: f32,
+    smoothed: Option<Vec2>,
+}
+
+impl InputFilter {
+    pub fn new(factor: f32) -> InputFilter {
+        InputFilter {
+            factor,
+            smoothed: None,
+        }
+    }
+
+    pub fn filter(&mut self, point: Vec2) -> Vec2 {
+        let smoothed = match self.smoothed {
+            Some(prev) if self.factor > 0. => prev.lerp(point, 1. - self.factor),
+            _ => point,
+        };
+        self.smoothed = Some(smoothed);
+        smoothed
+    }
+
+    /// forgets the history, the next point passes through as is
+    pub fn reset(&mut self) {
+        self.smoothed = None;
+    }
+}
+
+/// `path` of `width` in world coordinates moved to device pixels: points go through `view`,
+/// widths and dashes given in screen pixels scale with the zoom and `dpi_scale`
+fn screen_path(path: &BezierPath, width: f32, view: View, dpi_scale: f32) -> (BezierPath, f32) {
+    let mut path = path.clone();
+    path.transform(view.matrix());
+    let scale = view.scale * dpi_scale;
+    if let Some(pattern) = path.dash_pattern.as_mut() {
+        pattern.iter_mut().for_each(|len| *len *= scale);
+    }
+    (path, width * scale)
+}
+
+/// the paths `Strokes` draws with their quads in device pixels, everything but the GPU side.
+/// Quads of the paths below the active one are cached between updates, see `update_active`
+pub struct Canvas {
+    /// separate strokes with their widths, drawn in order. Never empty, the last one is
+    /// the active path mouse input and the setters go to
+    paths: Vec<(BezierPath, f32)>,
+    view: View,
+    vertices: Vec<Vertex>,
+    indices: Vec<u32>,
+    /// data of every curve when drawn instanced, see `Strokes::new_instanced`
+    instances: Option<Vec<CurveInstance>>,
+    /// lengths of `vertices` and `indices` before the active path, `None` once the paths
+    /// below it or the view changed since the last `update`
+    active_offset: Option<(usize, usize)>,
+}
+
+impl Canvas {
+    /// one empty active path, with room for the quads of `max_curves_num` curves. An
+    /// `instanced` canvas also keeps a `CurveInstance` per curve
+    pub fn new(max_curves_num: usize, instanced: bool) -> Canvas {
+        Canvas {
+            paths: vec![(BezierPath::default(), 10.)],
+            view: View::default(),
+            vertices: Vec::with_capacity(max_curves_num * 4),
+            indices: Vec::with_capacity(max_curves_num * 6),
+            instances: if instanced {
+                Some(Vec::with_capacity(max_curves_num))
+            } else {
+                None
+            },
+            active_offset: None,
+        }
+    }
+
+    pub fn path(&self) -> &BezierPath {
+        &self.paths.last().unwrap().0
+    }
+
+    pub fn path_mut(&mut self) -> &mut BezierPath {
+        &mut self.paths.last_mut().unwrap().0
+    }
+
+    pub fn paths(&self) -> &[(BezierPath, f32)] {
+        &self.paths
+    }
+
+    /// finishes the active path, following points go to a new one with the same style
+    pub fn new_path(&mut self) {
+        let (active, width) = self.paths.last().unwrap();
+        let mut path = BezierPath::default();
+        path.color = active.color;
+        path.gradient = active.gradient;
+        path.cap = active.cap;
+        path.join = active.join;
+        path.dash_pattern = active.dash_pattern.clone();
+        let width = *width;
+        self.paths.push((path, width));
+        self.active_offset = None;
+    }
+
+    /// adds a finished path below the active one
+    pub fn push_path(&mut self, path: BezierPath, width: f32) {
+        let index = self.paths.len() - 1;
+        self.paths.insert(index, (path, width.max(MIN_PATH_WIDTH)));
+        self.active_offset = None;
+    }
+
+    pub fn view(&self) -> View {
+        self.view
+    }
+
+    pub fn set_view(&mut self, view: View) {
+        self.view = view;
+        self.active_offset = None;
+    }
+
+    /// paths are kept in world coordinates, strokes are built in device pixels
+    /// so widths and dashes scale with the zoom and the dpi and edges stay sharp
+    pub fn screen_paths(&self, dpi_scale: f32) -> Vec<(BezierPath, f32)> {
+        self.paths
+            .iter()
+            .map(|(path, width)| screen_path(path, *width, self.view, dpi_scale))
+            .collect()
+    }
+
+    pub fn vertices(&self) -> &[Vertex] {
+        &self.vertices
+    }
+
+    pub fn indices(&self) -> &[u32] {
+        &self.indices
+    }
+
+    pub fn instances(&self) -> Option<&[CurveInstance]> {
+        self.instances.as_deref()
+    }
+
+    /// rebuilds the quads of every path with antialiased edges `aa_width` device pixels wide
+    pub fn update(&mut self, dpi_scale: f32, aa_width: f32, mode: PrimitiveMode) {
+        self.vertices.clear();
+        self.indices.clear();
+        let paths = self.screen_paths(dpi_scale);
+        let last = paths.len() - 1;
+        for (i, (path, width)) in paths.iter().enumerate() {
+            if i == last {
+                self.active_offset = Some((self.vertices.len(), self.indices.len()));
+            }
+            push_path_vertices(
+                &mut self.vertices,
+                &mut self.indices,
+                path,
+                *width,
+                mode,
+                aa_width,
+            );
+        }
+        self.update_instances(0);
+    }
+
+    /// rebuilds only the quads of the active path, the ones of the paths below it are kept
+    /// from the previous update. Falls back to `update` when those paths or the view changed
+    /// since
+    pub fn update_active(&mut self, dpi_scale: f32, aa_width: f32, mode: PrimitiveMode) {
+        let (vertices_len, indices_len) = match self.active_offset {
+            Some(offset) => offset,
+            None => return self.update(dpi_scale, aa_width, mode),
+        };
+        self.vertices.truncate(vertices_len);
+        self.indices.truncate(indices_len);
+        let (path, width) = self.paths.last().unwrap();
+        let (path, width) = screen_path(path, *width, self.view, dpi_scale);
+        push_path_vertices(
+            &mut self.vertices,
+            &mut self.indices,
+            &path,
+            width,
+            mode,
+            aa_width,
+        );
+        self.update_instances(vertices_len);
+    }
+
+    /// instances of the quads from vertex `from` on, the ones before are kept
+    fn update_instances(&mut self, from: usize) {
+        if let Some(instances) = self.instances.as_mut() {
+            instances.truncate(from / 4);
+            instances.extend(
+                self.vertices[from..]
+                    .chunks(4)
+                    .map(CurveInstance::from_quad),
+            );
+        }
+    }
+}
 
 pub struct Strokes {
-    pipeline: Pipeline,
+    /// one per `PrimitiveMode`, in the order of its variants
+    pipelines: [Pipeline; 2],
+    primitive_mode: PrimitiveMode,
     bindings: Bindings,
-    vertices: Vec<Vertex>,
-    indices: Vec<u16>,
     vertex_buffer: Buffer,
     index_buffer: Buffer,
-    path_width: f32,
-    path: BezierPath,
+    /// what is drawn, uploaded to the buffers on every update
+    canvas: Canvas,
+    /// last cursor position in screen pixels, wheel events don't carry it
+    cursor: Vec2,
+    /// space is held, left button drags pan the view instead of stroking
+    pan_key: bool,
+    /// screen position the pan drag continues from
+    drag: Option<Vec2>,
+    /// path index and point being dragged
+    grab: Option<(usize, Handle)>,
+    /// right button is held, cursor moves erase curves
+    erasing: bool,
+    input_filter: InputFilter,
+    /// width in pixels over which stroke edges fade out
+    aa_width: f32,
+    /// device pixels per screen pixel, see `set_dpi_scale`
+    dpi_scale: f32,
+    /// target of `render_to_texture` with its size, reused while the size stays the same
+    offscreen: Option<(RenderPass, u32, u32)>,
+    /// `date::now()` at creation, the `time` uniform counts from it
+    start_time: f64,
 
     timer: Timer,
+    /// frames per second averaged by `timer`, see `fps`
+    fps: Option<f32>,
 }
 
 impl Strokes {
-    pub fn new(ctx: &mut Context, max_curves_num: usize) -> Strokes {
+    /// fails if the shaders don't compile on the GPU driver. Buffers are allocated for
+    /// `max_curves_num` curves up front and grow when more are drawn
+    pub fn new(ctx: &mut Context, max_curves_num: usize) -> Result<Strokes, ShaderError> {
+        // compiled first so failing drivers don't leak the buffers
+        let shader = Shader::new(ctx, shader::VERTEX, shader::FRAGMENT, shader::meta())?;
+
+        // every curve is a quad of 4 vertices and 6 indices
         let vertex_buffer = Buffer::stream(
             ctx,
             BufferType::VertexBuffer,
-            max_curves_num * std::mem::size_of::<Vertex>(),
+            max_curves_num * 4 * std::mem::size_of::<Vertex>(),
         );
 
-        let index_buffer = Buffer::stream(
+        // u32 indices, u16 would wrap after 16k curves
+        let index_buffer = Buffer::index_stream(
             ctx,
-            BufferType::IndexBuffer,
-            max_curves_num * std::mem::size_of::<u16>(),
+            IndexType::Int,
+            max_curves_num * 6 * std::mem::size_of::<u32>(),
         );
 
         let bindings = Bindings {
@@ -40,9 +337,7 @@ impl Strokes {
             index_buffer,
             images: vec![],
         };
-        let shader = Shader::new(ctx, shader::VERTEX, shader::FRAGMENT, shader::meta()).unwrap();
-
-        let pipeline = Pipeline::with_params(
+        let pipelines = shader::pipelines(
             ctx,
             &[BufferLayout::default()],
             &[
@@ -51,74 +346,621 @@ impl Strokes {
                 VertexAttribute::new("control", VertexFormat::Float2),
                 VertexAttribute::new("c", VertexFormat::Float2),
                 VertexAttribute::new("thickness", VertexFormat::Float1),
+                VertexAttribute::new("end_thickness", VertexFormat::Float1),
+                VertexAttribute::new("color", VertexFormat::Float4),
+                VertexAttribute::new("end_color", VertexFormat::Float4),
+                VertexAttribute::new("caps", VertexFormat::Float2),
+                VertexAttribute::new("dash_offset", VertexFormat::Float1),
+                VertexAttribute::new("dash", VertexFormat::Float4),
             ],
             shader,
-            PipelineParams {
-                color_blend: Some(BlendState::new(
-                    Equation::Add,
-                    BlendFactor::Value(BlendValue::SourceAlpha),
-                    BlendFactor::OneMinusValue(BlendValue::SourceAlpha),
-                )),
-                ..Default::default()
-            },
         );
-        let path = BezierPath::default();
+        Ok(Strokes::with_pipelines(
+            ctx,
+            max_curves_num,
+            pipelines,
+            bindings,
+            false,
+        ))
+    }
 
+    /// same as `new` but every curve is drawn as an instance of one shared quad, so the curve
+    /// data is uploaded once per curve instead of once per each of its 4 vertices.
+    /// Needs instancing support of the GPU, see `benches/tessellation.rs` for upload sizes
+    pub fn new_instanced(ctx: &mut Context, max_curves_num: usize) -> Result<Strokes, ShaderError> {
+        let shader = Shader::new(
+            ctx,
+            shader::VERTEX_INSTANCED,
+            shader::FRAGMENT,
+            shader::meta(),
+        )?;
+
+        // corner ids of the quad template, positions come from the instances
+        let corner_buffer = Buffer::immutable(ctx, BufferType::VertexBuffer, &[0f32, 1., 2., 3.]);
+        // indices of the template change with the primitive mode, 6 at most
+        let index_buffer =
+            Buffer::index_stream(ctx, IndexType::Int, 6 * std::mem::size_of::<u32>());
+        index_buffer.update(ctx, &PrimitiveMode::default().quad_indices(0, 1));
+        let instance_buffer = Buffer::stream(
+            ctx,
+            BufferType::VertexBuffer,
+            max_curves_num * std::mem::size_of::<CurveInstance>(),
+        );
+
+        let bindings = Bindings {
+            vertex_buffers: vec![corner_buffer, instance_buffer],
+            index_buffer,
+            images: vec![],
+        };
+        let pipelines = shader::pipelines(
+            ctx,
+            &[
+                BufferLayout::default(),
+                BufferLayout {
+                    step_func: VertexStep::PerInstance,
+                    ..Default::default()
+                },
+            ],
+            &[
+                VertexAttribute::with_buffer("corner", VertexFormat::Float1, 0),
+                VertexAttribute::with_buffer("corners01", VertexFormat::Float4, 1),
+                VertexAttribute::with_buffer("corners23", VertexFormat::Float4, 1),
+                VertexAttribute::with_buffer("a", VertexFormat::Float2, 1),
+                VertexAttribute::with_buffer("control", VertexFormat::Float2, 1),
+                VertexAttribute::with_buffer("c", VertexFormat::Float2, 1),
+                VertexAttribute::with_buffer("thickness", VertexFormat::Float1, 1),
+                VertexAttribute::with_buffer("end_thickness", VertexFormat::Float1, 1),
+                VertexAttribute::with_buffer("color", VertexFormat::Float4, 1),
+                VertexAttribute::with_buffer("end_color", VertexFormat::Float4, 1),
+                VertexAttribute::with_buffer("caps", VertexFormat::Float2, 1),
+                VertexAttribute::with_buffer("dash_offset", VertexFormat::Float1, 1),
+                VertexAttribute::with_buffer("dash", VertexFormat::Float4, 1),
+            ],
+            shader,
+        );
+        Ok(Strokes::with_pipelines(
+            ctx,
+            max_curves_num,
+            pipelines,
+            bindings,
+            true,
+        ))
+    }
+
+    /// the last vertex buffer of `bindings` is the one updated with the strokes
+    fn with_pipelines(
+        ctx: &mut Context,
+        max_curves_num: usize,
+        pipelines: [Pipeline; 2],
+        bindings: Bindings,
+        instanced: bool,
+    ) -> Strokes {
+        let vertex_buffer = *bindings.vertex_buffers.last().unwrap();
+        let index_buffer = bindings.index_buffer;
         let mut stage = Strokes {
-            pipeline,
+            pipelines,
+            primitive_mode: PrimitiveMode::default(),
             bindings,
-            indices: Vec::with_capacity(max_curves_num * 6),
-            vertices: Vec::with_capacity(max_curves_num * 4),
             vertex_buffer,
             index_buffer,
-            path,
-            path_width: 10.,
+            canvas: Canvas::new(max_curves_num, instanced),
+            cursor: Vec2::zero(),
+            pan_key: false,
+            drag: None,
+            grab: None,
+            erasing: false,
+            input_filter: InputFilter::default(),
+            aa_width: DEFAULT_AA_WIDTH,
+            dpi_scale: 1.,
+            offscreen: None,
+            start_time: date::now(),
             timer: Timer::new(100),
+            fps: None,
         };
         stage.update_buffers(ctx);
         stage
     }
 
+    fn path(&self) -> &BezierPath {
+        self.canvas.path()
+    }
+
+    fn path_mut(&mut self) -> &mut BezierPath {
+        self.canvas.path_mut()
+    }
+
+    pub fn paths(&self) -> &[(BezierPath, f32)] {
+        self.canvas.paths()
+    }
+
+    /// finishes the active path, following input goes to a new one with the same style
+    pub fn new_path(&mut self) {
+        self.canvas.new_path();
+        self.input_filter.reset();
+    }
+
+    /// adds a finished path below the active one
+    pub fn push_path(&mut self, path: BezierPath, width: f32) {
+        self.canvas.push_path(path, width);
+    }
+
+    /// rgba color of the active path, applied on the next buffers update
+    pub fn set_color(&mut self, color: Vec4) {
+        self.path_mut().color = color;
+    }
+
+    /// rgba colors at the start and the end of the active path, `None` draws it
+    /// in the flat color. Applied on the next buffers update
+    pub fn set_gradient(&mut self, gradient: Option<(Vec4, Vec4)>) {
+        self.path_mut().gradient = gradient;
+    }
+
+    /// on and off dash lengths in pixels (see `Vertex::dash` for the limits),
+    /// `None` draws solid strokes. Applies to the active path on the next buffers update
+    pub fn set_dash_pattern(&mut self, pattern: Option<Vec<f32>>) {
+        self.path_mut().dash_pattern = pattern;
+    }
+
+    /// width of the active path in pixels, clamped to `MIN_PATH_WIDTH`.
+    /// Applied on the next buffers update
+    pub fn set_path_width(&mut self, width: f32) {
+        self.canvas.paths.last_mut().unwrap().1 = width.max(MIN_PATH_WIDTH);
+    }
+
+    pub fn path_width(&self) -> f32 {
+        self.canvas.paths.last().unwrap().1
+    }
+
+    /// ends of the active path, applied on the next buffers update
+    pub fn set_cap_style(&mut self, cap: CapStyle) {
+        self.path_mut().cap = cap;
+    }
+
+    /// joins between the curves of the active path, applied on the next buffers update
+    pub fn set_join_style(&mut self, join: JoinStyle) {
+        self.path_mut().join = join;
+    }
+
+    /// the topmost path point within `HANDLE_RADIUS` screen pixels of `point` in world
+    /// coordinates
+    pub fn handle_at(&self, point: Vec2) -> Option<(usize, Handle)> {
+        let radius = HANDLE_RADIUS * self.dpi_scale / self.canvas.view.scale;
+        self.canvas
+            .paths
+            .iter()
+            .enumerate()
+            .rev()
+            .find_map(|(i, (path, _))| path.handle_at(point, radius).map(|handle| (i, handle)))
+    }
+
+    /// weight of previous points when smoothing clicked points, in [0, 1).
+    /// 0 (the default) uses points as clicked, see `InputFilter`
+    pub fn set_input_smoothing(&mut self, factor: f32) {
+        self.input_filter.factor = factor.clamp(0., 0.99);
+    }
+
+    /// width in pixels of the antialiased edge centered on the stroke outlines,
+    /// `DEFAULT_AA_WIDTH` by default. Wider edges look smoother on high dpi screens.
+    /// Clamped to `MIN_AA_WIDTH`, quads are padded by `aa_margin` from the next update
+    pub fn set_aa_width(&mut self, width: f32) {
+        self.aa_width = width.max(MIN_AA_WIDTH);
+        self.canvas.active_offset = None;
+    }
+
+    /// device pixels per screen pixel, 1 by default. Points come in device pixels while
+    /// widths, dashes, the antialiased edge and `HANDLE_RADIUS` are in screen pixels, so a
+    /// 10 pixel stroke looks the same at any dpi. Clamped to `MIN_DPI_SCALE`,
+    /// applied on the next buffers update
+    pub fn set_dpi_scale(&mut self, scale: f32) {
+        self.dpi_scale = scale.max(MIN_DPI_SCALE);
+        self.canvas.active_offset = None;
+    }
+
+    /// how quads of curves are assembled from triangles, indexed triangles by default.
+    /// Strips cut the indices of `new_instanced` strokes from 6 to 4 per curve but barely
+    /// change them otherwise, see `PrimitiveMode`. Buffers are rebuilt right away
+    pub fn set_primitive_mode(&mut self, ctx: &mut Context, mode: PrimitiveMode) {
+        self.primitive_mode = mode;
+        if self.canvas.instances.is_some() {
+            self.index_buffer.update(ctx, &mode.quad_indices(0, 1));
+        }
+        self.update_buffers(ctx);
+    }
+
+    /// `aa_width` in device pixels
+    fn device_aa_width(&self) -> f32 {
+        self.aa_width * self.dpi_scale
+    }
+
+    /// wipes the canvas, buffers are refreshed right away so no stale curves are drawn.
+    /// The style of the active path is kept
+    pub fn clear(&mut self, ctx: &mut Context) {
+        let last = self.canvas.paths.len() - 1;
+        self.canvas.paths.drain(..last);
+        self.path_mut().clear();
+        self.input_filter.reset();
+        self.update_buffers(ctx);
+    }
+
+    /// removes the last committed curve of the active path
+    pub fn undo(&mut self, ctx: &mut Context) {
+        self.path_mut().undo();
+        self.update_active_buffers(ctx);
+    }
+
+    /// restores the curve removed by the last `undo` of the active path
+    pub fn redo(&mut self, ctx: &mut Context) {
+        self.path_mut().redo();
+        self.update_active_buffers(ctx);
+    }
+
+    /// removes every curve passing closer than `radius` to world point `p`, erased curves
+    /// leave gaps in their paths and paths left empty are dropped. Buffers are refreshed
+    pub fn erase_at(&mut self, p: Vec2, radius: f32, ctx: &mut Context) {
+        erase_curves(&mut self.canvas.paths, p, radius);
+        // indices of a grabbed handle may point to erased curves
+        self.grab = None;
+        self.update_buffers(ctx);
+    }
+
+    /// `erase_at` screen point `p` with `ERASER_RADIUS`
+    fn erase_at_cursor(&mut self, p: Vec2, ctx: &mut Context) {
+        let radius = ERASER_RADIUS * self.dpi_scale / self.canvas.view.scale;
+        self.erase_at(self.canvas.view.to_world(p), radius, ctx);
+    }
+
+    /// frame rate averaged over the last 100 `draw` calls, `None` until that many were made
+    pub fn fps(&self) -> Option<f32> {
+        self.fps
+    }
+
+    /// seconds since the strokes were created, passed to the shader as `time` on every draw
+    pub fn time(&self) -> f32 {
+        (date::now() - self.start_time) as f32
+    }
+
+    pub fn view(&self) -> View {
+        self.canvas.view()
+    }
+
+    pub fn set_view(&mut self, view: View) {
+        self.canvas.set_view(view);
+    }
+
+    /// zooms and pans so every path fits the screen with `FIT_MARGIN` around, the zoom
+    /// staying within `MIN_ZOOM..=MAX_ZOOM`. Does nothing when there is nothing drawn
+    pub fn fit_view(&mut self, ctx: &mut Context) {
+        let bounds = self
+            .canvas
+            .paths
+            .iter()
+            .filter_map(|(path, _)| path.bounding_box())
+            .reduce(|(mi0, ma0), (mi1, ma1)| (mi0.min(mi1), ma0.max(ma1)));
+        let (mi, ma) = match bounds {
+            Some(bounds) => bounds,
+            None => return,
+        };
+        let (w, h) = ctx.screen_size();
+        self.canvas.view = View::fitting(mi, ma, vec2(w, h), FIT_MARGIN * self.dpi_scale);
+        self.update_buffers(ctx);
+    }
+
+    /// saves the drawing as seen on screen over `background` to a png of
+    /// `TEXTURE_WIDTH` x `TEXTURE_HEIGHT` pixels, anchored at the top left corner of the
+    /// window. Uses the software rasterizer, see `raster::render_to_buffer` for what differs
+    /// from the live view and `raster::render_paths` for `supersample`
+    #[cfg(all(feature = "render", feature = "image"))]
+    pub fn export_png(
+        &self,
+        path: &std::path::Path,
+        background: [f32; 4],
+        supersample: u32,
+    ) -> std::io::Result<()> {
+        crate::raster::save_png(
+            path,
+            &self.canvas.screen_paths(self.dpi_scale),
+            crate::TEXTURE_WIDTH,
+            crate::TEXTURE_HEIGHT,
+            background.into(),
+            supersample,
+        )
+    }
+
+    /// draws the strokes into an offscreen rgba texture of `size` pixels, or of the screen
+    /// size if `None`. The texture shows the region a window of that size would.
+    /// It's owned by `Strokes` and reused by the next call of the same size, the previous
+    /// one is deleted when the size changes. Rows are stored bottom to top as usual in GL
+    pub fn render_to_texture(&mut self, ctx: &mut Context, size: Option<(u32, u32)>) -> Texture {
+        let (width, height) = texture_size(size, ctx.screen_size());
+        let pass = match self.offscreen {
+            Some((pass, w, h)) if (w, h) == (width, height) => pass,
+            _ => {
+                if let Some((pass, _, _)) = self.offscreen.take() {
+                    pass.delete(ctx);
+                }
+                let texture = Texture::new_render_texture(
+                    ctx,
+                    TextureParams {
+                        width,
+                        height,
+                        ..Default::default()
+                    },
+                );
+                let pass = RenderPass::new(ctx, texture, None);
+                self.offscreen = Some((pass, width, height));
+                pass
+            }
+        };
+        ctx.begin_pass(pass, PassAction::clear_color(0., 0., 0., 0.));
+        self.draw_strokes(ctx, vec2(width as f32, height as f32));
+        ctx.end_render_pass();
+        pass.texture(ctx)
+    }
+
+    fn draw_strokes(&mut self, ctx: &mut Context, resolution: Vec2) {
+        ctx.apply_pipeline(&self.pipelines[self.primitive_mode as usize]);
+        ctx.apply_uniforms(&shader::Uniforms {
+            resolution,
+            aa_width: self.device_aa_width(),
+            time: self.time(),
+        });
+        ctx.apply_bindings(&self.bindings);
+        match self.canvas.instances.as_ref() {
+            Some(instances) => {
+                let quad = self.primitive_mode.quad_indices(0, 1);
+                ctx.draw(0, quad.len() as i32, instances.len() as i32)
+            }
+            None => ctx.draw(0, self.canvas.indices.len() as i32, 1),
+        }
+    }
+
     pub fn update_buffers(&mut self, ctx: &mut Context) {
-        let (vertices, indices) = self.path.vertices(self.path_width);
-        self.vertices = vertices;
-        self.indices = indices;
-        self.vertex_buffer.update(ctx, &self.vertices);
-        self.index_buffer.update(ctx, &self.indices);
+        self.canvas
+            .update(self.dpi_scale, self.device_aa_width(), self.primitive_mode);
+        self.upload(ctx);
     }
-}
 
-#[derive(Debug, Default)]
-pub struct Shape {
-    pub regular: Vec<Vec2>,
-    pub holes: Vec<Vec<Vec2>>,
+    /// rebuilds only the quads of the active path, see `Canvas::update_active`. Buffers are
+    /// still uploaded whole, miniquad updates them from the start
+    pub fn update_active_buffers(&mut self, ctx: &mut Context) {
+        self.canvas
+            .update_active(self.dpi_scale, self.device_aa_width(), self.primitive_mode);
+        self.upload(ctx);
+    }
+
+    /// buffers are grown when the strokes outgrow them, see `grown_size`
+    fn upload(&mut self, ctx: &mut Context) {
+        let canvas = &self.canvas;
+        let (vertices_size, indices_size) = match canvas.instances.as_ref() {
+            // the quad template never changes, only the instances are uploaded
+            Some(instances) => (std::mem::size_of_val(instances.as_slice()), 0),
+            None => (
+                std::mem::size_of_val(canvas.vertices.as_slice()),
+                std::mem::size_of_val(canvas.indices.as_slice()),
+            ),
+        };
+        if let Some(size) = grown_size(self.vertex_buffer.size(), vertices_size) {
+            let buffer = Buffer::stream(ctx, BufferType::VertexBuffer, size);
+            self.replace_vertex_buffer(buffer);
+        }
+        if let Some(size) = grown_size(self.index_buffer.size(), indices_size) {
+            let buffer = Buffer::index_stream(ctx, IndexType::Int, size);
+            self.replace_index_buffer(buffer);
+        }
+        match self.canvas.instances.as_ref() {
+            Some(instances) => self.vertex_buffer.update(ctx, instances),
+            None => {
+                self.vertex_buffer.update(ctx, &self.canvas.vertices);
+                self.index_buffer.update(ctx, &self.canvas.indices);
+            }
+        }
+    }
+
+    /// swaps the buffer the vertices or instances are uploaded to, the old one is deleted. The
+    /// new one has to be created first so it gets a different gl name, miniquad skips
+    /// rebinding buffers of the name already bound
+    fn replace_vertex_buffer(&mut self, buffer: Buffer) {
+        self.vertex_buffer.delete();
+        self.vertex_buffer = buffer;
+        *self.bindings.vertex_buffers.last_mut().unwrap() = buffer;
+    }
+
+    /// same as `replace_vertex_buffer` for the index buffer
+    fn replace_index_buffer(&mut self, buffer: Buffer) {
+        self.index_buffer.delete();
+        self.index_buffer = buffer;
+        self.bindings.index_buffer = buffer;
+    }
 }
 
-impl Shape {
-    pub fn from_regular(regular: Vec<Vec2>) -> Shape {
-        Shape {
-            regular,
-            holes: Vec::new(),
+/// removes curves of `paths` closer than `radius` to `p`, see `Strokes::erase_at`.
+/// The last path is the one being drawn and stays even when left empty
+fn erase_curves(paths: &mut Vec<(BezierPath, f32)>, p: Vec2, radius: f32) {
+    let last = paths.len() - 1;
+    for (path, _) in paths.iter_mut() {
+        let hits: Vec<usize> = (0..path.curves.len())
+            .filter(|&i| path.curves[i].distance(p) < radius)
+            .collect();
+        for &i in hits.iter().rev() {
+            path.remove_curve(i);
+        }
+        if !hits.is_empty() {
+            path.closed = false;
         }
     }
+    let mut index = 0;
+    paths.retain(|(path, _)| {
+        index += 1;
+        index - 1 == last || !path.curves.is_empty()
+    });
+}
+
+/// `size` of an offscreen target if given, else the `screen` size rounded down to whole
+/// pixels, at least one by one
+fn texture_size(size: Option<(u32, u32)>, screen: (f32, f32)) -> (u32, u32) {
+    let (width, height) = size.unwrap_or((screen.0 as u32, screen.1 as u32));
+    (width.max(1), height.max(1))
+}
+
+/// appends quads of `path` of `width` with their indices in `mode` to the ones of the paths
+/// before it
+fn push_path_vertices(
+    vertices: &mut Vec<Vertex>,
+    indices: &mut Vec<u32>,
+    path: &BezierPath,
+    width: f32,
+    mode: PrimitiveMode,
+    aa_width: f32,
+) {
+    let (path_vertices, _) = path.vertices_antialiased(width, width, aa_width);
+    let first = vertices.len() / 4;
+    indices.extend(mode.quad_indices(first, path_vertices.len() / 4));
+    vertices.extend(path_vertices);
+}
+
+/// curve from the end of `path` through its pending control point to `cursor`. A preview
+/// collapsed into a point has no extent to draw, it's skipped rather than sent to the shader
+fn drawable_preview(path: &BezierPath, cursor: Vec2) -> Option<QuadCurve> {
+    path.preview_curve(cursor)
+        .filter(|curve| !curve.is_degenerate(DEGENERATE_EPSILON))
 }
 
+/// size in bytes to replace a buffer of `capacity` bytes with when `needed` bytes don't fit,
+/// `None` when they do. Miniquad can't update a buffer past its size, growing by at least
+/// twice the capacity keeps reallocations rare while a drawing grows
+fn grown_size(capacity: usize, needed: usize) -> Option<usize> {
+    if needed <= capacity {
+        None
+    } else {
+        Some(needed.max(capacity * 2))
+    }
+}
+
+/// what pressing a mouse button does
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum ButtonAction {
+    /// strokes or drags curve points
+    Draw,
+    Erase,
+    Pan,
+}
+
+/// left button draws, right button erases, middle button (or left with `pan_key` held) pans.
+/// Other buttons do nothing
+fn button_action(button: MouseButton, pan_key: bool) -> Option<ButtonAction> {
+    match button {
+        MouseButton::Left if pan_key => Some(ButtonAction::Pan),
+        MouseButton::Left => Some(ButtonAction::Draw),
+        MouseButton::Right => Some(ButtonAction::Erase),
+        MouseButton::Middle => Some(ButtonAction::Pan),
+        _ => None,
+    }
+}
+
+/// left button strokes and drags curve points, right button erases, middle button
+/// (or left with space held) pans, see `button_action`
 impl EventHandler for Strokes {
-    fn mouse_button_down_event(&mut self, ctx: &mut Context, _button: MouseButton, x: f32, y: f32) {
-        self.path.stroke(vec2(x, y));
-        self.update_buffers(ctx);
+    fn mouse_button_down_event(&mut self, ctx: &mut Context, button: MouseButton, x: f32, y: f32) {
+        match button_action(button, self.pan_key) {
+            Some(ButtonAction::Pan) => {
+                self.drag = Some(vec2(x, y));
+                return;
+            }
+            Some(ButtonAction::Erase) => {
+                self.erasing = true;
+                self.erase_at_cursor(vec2(x, y), ctx);
+                return;
+            }
+            Some(ButtonAction::Draw) => {}
+            None => return,
+        }
+        let point = self.canvas.view.to_world(vec2(x, y));
+        // points of existing curves can be dragged unless a curve is half way drawn
+        if self.path().control.is_none() {
+            self.grab = self.handle_at(point);
+            if self.grab.is_some() {
+                return;
+            }
+        }
+        let point = self.input_filter.filter(point);
+        self.path_mut().stroke(point);
+        self.update_active_buffers(ctx);
+    }
+
+    fn mouse_button_up_event(
+        &mut self,
+        _ctx: &mut Context,
+        _button: MouseButton,
+        _x: f32,
+        _y: f32,
+    ) {
+        self.drag = None;
+        self.grab = None;
+        self.erasing = false;
     }
 
     fn mouse_motion_event(&mut self, ctx: &mut Context, x: f32, y: f32) {
-        let mut to_undo = false;
-        if self.path.control.is_some() {
-            to_undo = true;
-            self.path.stroke(vec2(x, y));
+        self.cursor = vec2(x, y);
+        if let Some(from) = self.drag {
+            self.canvas.view.pan(self.cursor - from);
+            self.drag = Some(self.cursor);
+            self.canvas.active_offset = None;
+        }
+        if self.erasing {
+            // erasing rebuilds every buffer already
+            self.erase_at_cursor(self.cursor, ctx);
+            return;
+        }
+        if let Some((index, handle)) = self.grab {
+            let to = self.canvas.view.to_world(self.cursor);
+            self.canvas.paths[index].0.move_handle(handle, to);
+            if index != self.canvas.paths.len() - 1 {
+                self.canvas.active_offset = None;
+            }
+        }
+        // preview curve is pushed directly, going through stroke/undo would
+        // clobber the redo history
+        match drawable_preview(self.path(), self.canvas.view.to_world(self.cursor)) {
+            Some(curve) => {
+                self.path_mut().curves.push(curve);
+                self.update_active_buffers(ctx);
+                self.path_mut().curves.pop();
+            }
+            None => self.update_active_buffers(ctx),
         }
+    }
 
-        self.update_buffers(ctx);
-        if to_undo {
-            self.path.undo();
+    fn mouse_wheel_event(&mut self, ctx: &mut Context, _x: f32, y: f32) {
+        if y != 0. {
+            self.canvas
+                .view
+                .zoom_at(self.cursor, ZOOM_STEP.powf(y.signum()));
+            self.update_buffers(ctx);
+        }
+    }
+
+    fn key_down_event(
+        &mut self,
+        ctx: &mut Context,
+        keycode: KeyCode,
+        keymods: KeyMods,
+        _repeat: bool,
+    ) {
+        match keycode {
+            KeyCode::Z if keymods.ctrl && keymods.shift => self.redo(ctx),
+            KeyCode::Z if keymods.ctrl => self.undo(ctx),
+            KeyCode::Y if keymods.ctrl => self.redo(ctx),
+            KeyCode::C | KeyCode::Delete if !keymods.ctrl => self.clear(ctx),
+            KeyCode::Space => self.pan_key = true,
+            KeyCode::Enter => self.new_path(),
+            KeyCode::F => self.fit_view(ctx),
+            _ => {}
+        }
+    }
+
+    fn key_up_event(&mut self, _ctx: &mut Context, keycode: KeyCode, _keymods: KeyMods) {
+        if keycode == KeyCode::Space {
+            self.pan_key = false;
         }
     }
 
@@ -126,19 +968,15 @@ impl EventHandler for Strokes {
 
     fn draw(&mut self, ctx: &mut Context) {
         let (w, h) = ctx.screen_size();
-        let size = vec2(w, h);
-        ctx.apply_pipeline(&self.pipeline);
-        ctx.apply_uniforms(&size);
-        ctx.apply_bindings(&self.bindings);
-        ctx.draw(0, self.indices.len() as i32, 1);
+        self.draw_strokes(ctx, vec2(w, h));
         if let Some(avg) = self.timer.tick() {
-            #[cfg(not(target_arch = "wasm32"))]
-            println!("{:?} fps", Duration::new(1, 0).as_nanos() / avg.as_nanos());
+            self.fps = Some(frames_per_second(avg));
         }
     }
 }
 
 mod shader {
+    use glam::Vec2;
     use miniquad::*;
 
     pub const VERTEX: &str = r#"# version 100
@@ -148,12 +986,24 @@ mod shader {
     attribute vec2 control;
     attribute vec2 c;
     attribute float thickness;
+    attribute float end_thickness;
+    attribute vec4 color;
+    attribute vec4 end_color;
+    attribute vec2 caps;
+    attribute float dash_offset;
+    attribute vec4 dash;
 
     varying vec2 af;
     varying vec2 controlf;
     varying vec2 cf;
     varying vec2 posf;
     varying float thicknessf;
+    varying float end_thicknessf;
+    varying vec4 colorf;
+    varying vec4 end_colorf;
+    varying vec2 capsf;
+    varying float dash_offsetf;
+    varying vec4 dashf;
 
     void main() {
         vec2 ps = vec2(2.* pos.x / resolution.x - 1., -2. * pos.y / resolution.y + 1.);
@@ -162,6 +1012,59 @@ mod shader {
         cf = c;
         posf = pos;
         thicknessf = thickness;
+        end_thicknessf = end_thickness;
+        colorf = color;
+        end_colorf = end_color;
+        capsf = caps;
+        dash_offsetf = dash_offset;
+        dashf = dash;
+        gl_Position = vec4(ps, 0., 1.);
+    }"#;
+
+    /// same as `VERTEX` with the position picked from the corners of the curve instance
+    pub const VERTEX_INSTANCED: &str = r#"# version 100
+    uniform vec2 resolution;
+    attribute float corner;
+    attribute vec4 corners01;
+    attribute vec4 corners23;
+    attribute vec2 a;
+    attribute vec2 control;
+    attribute vec2 c;
+    attribute float thickness;
+    attribute float end_thickness;
+    attribute vec4 color;
+    attribute vec4 end_color;
+    attribute vec2 caps;
+    attribute float dash_offset;
+    attribute vec4 dash;
+
+    varying vec2 af;
+    varying vec2 controlf;
+    varying vec2 cf;
+    varying vec2 posf;
+    varying float thicknessf;
+    varying float end_thicknessf;
+    varying vec4 colorf;
+    varying vec4 end_colorf;
+    varying vec2 capsf;
+    varying float dash_offsetf;
+    varying vec4 dashf;
+
+    void main() {
+        vec4 corners = corner < 1.5 ? corners01 : corners23;
+        vec2 pos = mod(corner, 2.) < 0.5 ? corners.xy : corners.zw;
+        vec2 ps = vec2(2.* pos.x / resolution.x - 1., -2. * pos.y / resolution.y + 1.);
+        af = a;
+        controlf = control;
+        cf = c;
+        posf = pos;
+        thicknessf = thickness;
+        end_thicknessf = end_thickness;
+        colorf = color;
+        end_colorf = end_color;
+        capsf = caps;
+        dash_offsetf = dash_offset;
+        dashf = dash;
         gl_Position = vec4(ps, 0., 1.);
     }"#;
 
@@ -172,16 +1075,45 @@ mod shader {
     varying vec2 cf;
     varying vec2 posf;
     varying float thicknessf;
+    varying float end_thicknessf;
+    varying vec4 colorf;
+    varying vec4 end_colorf;
+    varying vec2 capsf;
+    varying float dash_offsetf;
+    varying vec4 dashf;
+    uniform float aa_width;
+    // seconds since the strokes were created, for animated effects
+    uniform float time;
 
     
     float dot2( in vec2 v ) { return dot(v,v); }
 
-    float sdBezier( in vec2 pos, in vec2 A, in vec2 B, in vec2 C )
+    // 5 point Gauss-Legendre quadrature of the speed, same as QuadCurve::length_to
+    float arcLength( in vec2 A, in vec2 B, in vec2 C, in float t )
+    {
+        vec2 x = vec2(0.538469310, 0.906179846);
+        vec2 w = vec2(0.478628670, 0.236926885);
+        float h = t / 2.0;
+        float res = 0.568888889 * length(mix(B - A, C - B, h));
+        res += w.x * (length(mix(B - A, C - B, h - h*x.x)) + length(mix(B - A, C - B, h + h*x.x)));
+        res += w.y * (length(mix(B - A, C - B, h - h*x.y)) + length(mix(B - A, C - B, h + h*x.y)));
+        return 2.0 * res * h;
+    }
+
+    float sdBezier( in vec2 pos, in vec2 A, in vec2 B, in vec2 C, out float tc )
     {    
         vec2 a = B - A;
         vec2 b = A - 2.0*B + C;
         vec2 c = a * 2.0;
         vec2 d = A - pos;
+        // straight curve with the control in the middle (or all points coinciding),
+        // the cubic below would divide by zero. Same fallback as QuadCurve::closest_point
+        if (dot(b,b) < 1e-4)
+        {
+            float cc = dot(c,c);
+            tc = cc < 1e-4 ? 0.0 : clamp(-dot(d,c)/cc, 0.0, 1.0);
+            return length(d + c*tc);
+        }
         float kk = 1.0/dot(b,b);
         float kx = kk * dot(a,b);
         float ky = kk * (2.0*dot(a,a)+dot(d,b)) / 3.0;
@@ -198,6 +1130,7 @@ mod shader {
             vec2 uv = sign(x)*pow(abs(x), vec2(1.0/3.0));
             float t = clamp( uv.x+uv.y-kx, 0.0, 1.0 );
             res = dot2(d + (c + b*t)*t);
+            tc = t;
         }
         else
         {
@@ -206,8 +1139,10 @@ mod shader {
             float m = cos(v);
             float n = sin(v)*1.732050808;
             vec3  t = clamp(vec3(m+m,-n-m,n-m)*z-kx,0.0,1.0);
-            res = min( dot2(d+(c+b*t.x)*t.x),
-                       dot2(d+(c+b*t.y)*t.y) );
+            float dx = dot2(d+(c+b*t.x)*t.x);
+            float dy = dot2(d+(c+b*t.y)*t.y);
+            res = min(dx, dy);
+            tc = dx < dy ? t.x : t.y;
             // the third root cannot be the closest
             // res = min(res,dot2(d+(c+b*t.z)*t.z));
         }
@@ -215,23 +1150,399 @@ mod shader {
     }
 
     void main() {
-        vec4 color = vec4(1., 1., 1., 1.);
-        float d = sdBezier(posf, af, controlf, cf) - thicknessf;
-        float s = smoothstep(0., 1., -d);
-        if (d < 0.) {
-            color.a = s;
+        // width and color are interpolated by the parameter of the closest point on the curve
+        float t;
+        float dist = sdBezier(posf, af, controlf, cf, t);
+        float width = mix(thicknessf, end_thicknessf, t);
+        vec4 color = mix(colorf, end_colorf, t);
+        float d = dist - width;
+        // closest point at an open end, 1 is butt cap, 2 square, round comes for free
+        float cap = t <= 0. ? capsf.x : (t >= 1. ? capsf.y : 0.);
+        if (cap > 0.5) {
+            vec2 end = t <= 0. ? af : cf;
+            vec2 dir = t <= 0. ? af - controlf : cf - controlf;
+            if (dot(dir, dir) == 0.) {
+                dir = t <= 0. ? af - cf : cf - af;
+            }
+            dir = normalize(dir);
+            vec2 rel = posf - end;
+            float along = dot(rel, dir);
+            float across = abs(dir.x * rel.y - dir.y * rel.x);
+            d = cap < 1.5 ? max(along, across - width) : max(along, across) - width;
+        }
+        float period = dashf.x + dashf.y + dashf.z + dashf.w;
+        if (period > 0.) {
+            float along = mod(dash_offsetf + arcLength(af, controlf, cf, t), period);
+            if ((along >= dashf.x && along < dashf.x + dashf.y) || along >= dashf.x + dashf.y + dashf.z) {
+                discard;
+            }
+        }
+        // the edge fades out over aa_width centered on it, quads are padded by half of it
+        float s = smoothstep(-0.5 * aa_width, 0.5 * aa_width, -d);
+        if (d < 0.5 * aa_width) {
+            color.a *= s;
         } else {
             discard;
         }
         gl_FragColor = color;
     }"#;
 
+    /// values of the uniform block, copied as raw bytes by `apply_uniforms` so the
+    /// fields have to follow `UNIFORMS` in order and size without padding
+    #[repr(C)]
+    pub struct Uniforms {
+        pub resolution: Vec2,
+        pub aa_width: f32,
+        pub time: f32,
+    }
+
+    /// names and types of the shader uniforms, one per field of `Uniforms`
+    pub const UNIFORMS: [(&str, UniformType); 3] = [
+        ("resolution", UniformType::Float2),
+        ("aa_width", UniformType::Float1),
+        ("time", UniformType::Float1),
+    ];
+
+    /// bytes taken by `uniforms` in the block, `UniformType::size` isn't const
+    const fn block_size(uniforms: &[(&str, UniformType)]) -> usize {
+        let mut size = 0;
+        let mut i = 0;
+        while i < uniforms.len() {
+            size += match uniforms[i].1 {
+                UniformType::Float1 | UniformType::Int1 => 4,
+                UniformType::Float2 | UniformType::Int2 => 8,
+                UniformType::Float3 | UniformType::Int3 => 12,
+                UniformType::Float4 | UniformType::Int4 => 16,
+                UniformType::Mat4 => 64,
+            };
+            i += 1;
+        }
+        size
+    }
+
+    const _: () = assert!(std::mem::size_of::<Uniforms>() == block_size(&UNIFORMS));
+
+    /// a pipeline per `PrimitiveMode`, in the order of its variants
+    pub fn pipelines(
+        ctx: &mut Context,
+        buffer_layout: &[BufferLayout],
+        attributes: &[VertexAttribute],
+        shader: Shader,
+    ) -> [Pipeline; 2] {
+        let mut pipeline = |primitive_type| {
+            let params = PipelineParams {
+                primitive_type,
+                ..params()
+            };
+            Pipeline::with_params(ctx, buffer_layout, attributes, shader, params)
+        };
+        [
+            pipeline(PrimitiveType::Triangles),
+            pipeline(PrimitiveType::TriangleStrip),
+        ]
+    }
+
+    pub fn params() -> PipelineParams {
+        PipelineParams {
+            color_blend: Some(BlendState::new(
+                Equation::Add,
+                BlendFactor::Value(BlendValue::SourceAlpha),
+                BlendFactor::OneMinusValue(BlendValue::SourceAlpha),
+            )),
+            ..Default::default()
+        }
+    }
+
     pub fn meta() -> ShaderMeta {
         ShaderMeta {
             images: vec![],
             uniforms: UniformBlockLayout {
-                uniforms: vec![UniformDesc::new("resolution", UniformType::Float2)],
+                uniforms: UNIFORMS
+                    .iter()
+                    .map(|&(name, uniform_type)| UniformDesc::new(name, uniform_type))
+                    .collect(),
             }
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn line() -> BezierPath {
+        let mut path = BezierPath::default();
+        for &point in &[vec2(0., 0.), vec2(5., 0.), vec2(10., 0.)] {
+            path.stroke(point);
+        }
+        path
+    }
+
+    #[test]
+    fn path_width_reaches_vertex_thickness() {
+        let view = View::default();
+        for &width in &[MIN_PATH_WIDTH, 3., 25.] {
+            let (path, width) = screen_path(&line(), width, view, 1.);
+            let (vertices, _) = path.vertices_antialiased(width, width, DEFAULT_AA_WIDTH);
+            assert!(vertices
+                .iter()
+                .all(|v| v.thickness == width && v.end_thickness == width));
+        }
+    }
+
+    #[test]
+    fn screen_world_mapping() {
+        let mut view = View {
+            scale: 2.,
+            offset: vec2(10., -4.),
+        };
+        let world = vec2(3., 7.);
+        assert_eq!(view.to_screen(world), vec2(16., 10.));
+        assert_eq!(view.to_world(vec2(16., 10.)), world);
+        assert_eq!(view.matrix().transform_point2(world), view.to_screen(world));
+
+        // zooming keeps the point under the cursor in place
+        let cursor = vec2(100., 50.);
+        let under = view.to_world(cursor);
+        view.zoom_at(cursor, 1.5);
+        assert_eq!(view.scale, 3.);
+        assert!((view.to_screen(under) - cursor).length() < 1e-4);
+        view.zoom_at(cursor, 1000.);
+        assert_eq!(view.scale, MAX_ZOOM);
+
+        view.pan(vec2(5., 5.));
+        assert!((view.to_screen(under) - (cursor + vec2(5., 5.))).length() < 1e-3);
+    }
+
+    #[test]
+    fn two_paths_share_the_index_buffer() {
+        let (mut vertices, mut indices) = (vec![], vec![]);
+        let mut second = line();
+        second.stroke(vec2(15., 5.));
+        second.stroke(vec2(20., 20.));
+        for path in [line(), second] {
+            let mode = PrimitiveMode::Triangles;
+            push_path_vertices(&mut vertices, &mut indices, &path, 2., mode, 1.);
+        }
+        // one curve in the first path, two in the second
+        assert_eq!(vertices.len(), 3 * 4);
+        assert_eq!(indices.len(), 3 * 6);
+        assert_eq!(indices[6..12], [4, 5, 6, 4, 6, 7]);
+        assert_eq!(indices.iter().max(), Some(&11));
+    }
+
+    #[test]
+    fn buffers_grow_past_their_capacity() {
+        assert_eq!(grown_size(100, 0), None);
+        assert_eq!(grown_size(100, 100), None);
+        assert_eq!(grown_size(100, 101), Some(200));
+        assert_eq!(grown_size(100, 350), Some(350));
+        assert_eq!(grown_size(0, 24), Some(24));
+    }
+
+    #[test]
+    fn degenerate_preview_is_skipped() {
+        let mut collapsed = BezierPath::default();
+        collapsed.last = Some(vec2(5., 5.));
+        collapsed.control = Some(vec2(5., 5.));
+        assert_eq!(drawable_preview(&collapsed, vec2(5., 5.)), None);
+        assert!(drawable_preview(&collapsed, vec2(6., 5.)).is_some());
+        let mut path = BezierPath::default();
+        path.stroke(vec2(5., 5.));
+        path.stroke(vec2(10., 5.));
+        // collinear previews are drawn, their vertices stay finite
+        for &cursor in &[vec2(15., 5.), vec2(0., 5.), vec2(10., 5.)] {
+            let mut preview = path.clone();
+            preview
+                .curves
+                .push(drawable_preview(&path, cursor).unwrap());
+            let (mut vertices, mut indices) = (vec![], vec![]);
+            push_path_vertices(
+                &mut vertices,
+                &mut indices,
+                &preview,
+                2.,
+                PrimitiveMode::Triangles,
+                1.,
+            );
+            assert!(vertices
+                .iter()
+                .all(|v| v.position.x.is_finite() && v.position.y.is_finite()));
+        }
+    }
+
+    #[test]
+    fn texture_size_falls_back_to_the_screen() {
+        let screen = (800.5, 600.);
+        assert_eq!(texture_size(None, screen), (800, 600));
+        let size = Some((crate::TEXTURE_WIDTH, crate::TEXTURE_HEIGHT));
+        assert_eq!(texture_size(size, screen), (3840, 2160));
+        assert_eq!(texture_size(None, (0., 0.)), (1, 1));
+    }
+
+    #[test]
+    fn input_filter_reduces_variance() {
+        // a horizontal line with deterministic jitter
+        let noisy: Vec<Vec2> = (0..200)
+            .map(|i| vec2(i as f32, ((i * 7919) % 13) as f32 - 6.))
+            .collect();
+        let variance = |points: &[Vec2]| {
+            let mean = points.iter().map(|p| p.y).sum::<f32>() / points.len() as f32;
+            points.iter().map(|p| (p.y - mean).powi(2)).sum::<f32>() / points.len() as f32
+        };
+        let mut raw = InputFilter::new(0.);
+        let passed: Vec<Vec2> = noisy.iter().map(|&p| raw.filter(p)).collect();
+        assert_eq!(passed, noisy);
+        let mut smooth = InputFilter::new(0.8);
+        let smoothed: Vec<Vec2> = noisy.iter().map(|&p| smooth.filter(p)).collect();
+        assert!(variance(&smoothed) < variance(&noisy) / 4.);
+        smooth.reset();
+        assert_eq!(smooth.filter(vec2(3., 3.)), vec2(3., 3.));
+    }
+
+    #[test]
+    fn erasing_near_one_of_two_curves() {
+        let mut path = line();
+        path.stroke(vec2(15., 5.));
+        path.stroke(vec2(20., 20.));
+        let mut paths = vec![(path, 2.)];
+        erase_curves(&mut paths, vec2(19., 19.), 3.);
+        assert_eq!(paths.len(), 1);
+        assert_eq!(paths[0].0.curves, line().curves);
+        // an emptied path is dropped unless it's the one being drawn
+        let mut paths = vec![(line(), 2.), (line(), 2.), (BezierPath::default(), 2.)];
+        erase_curves(&mut paths, vec2(5., 0.), 1.);
+        assert_eq!(paths.len(), 1);
+        assert!(paths[0].0.curves.is_empty());
+    }
+
+    #[test]
+    fn uniforms_match_the_declared_block() {
+        let declared: usize = shader::UNIFORMS.iter().map(|(_, kind)| kind.size()).sum();
+        assert_eq!(std::mem::size_of::<shader::Uniforms>(), declared);
+        assert_eq!(
+            shader::meta().uniforms.uniforms.len(),
+            shader::UNIFORMS.len()
+        );
+    }
+
+    #[test]
+    fn thickness_scales_with_dpi_and_zoom() {
+        let view = View {
+            scale: 1.5,
+            offset: Vec2::zero(),
+        };
+        let mut dashed = line();
+        dashed.dash_pattern = Some(vec![4., 2.]);
+        let (path, width) = screen_path(&dashed, 10., view, 2.);
+        assert_eq!(width, 30.);
+        assert_eq!(path.dash_pattern, Some(vec![12., 6.]));
+        assert_eq!(path.curves[0].c, vec2(15., 0.));
+        let (vertices, _) = path.vertices(width);
+        assert!(vertices.iter().all(|v| v.thickness == 30.));
+        // without zoom and on a standard dpi screen widths stay as given
+        let (_, width) = screen_path(&dashed, 10., View::default(), 1.);
+        assert_eq!(width, 10.);
+    }
+
+    #[test]
+    fn mouse_buttons_pick_the_tool() {
+        assert_eq!(
+            button_action(MouseButton::Left, false),
+            Some(ButtonAction::Draw)
+        );
+        assert_eq!(
+            button_action(MouseButton::Right, false),
+            Some(ButtonAction::Erase)
+        );
+        assert_eq!(
+            button_action(MouseButton::Middle, false),
+            Some(ButtonAction::Pan)
+        );
+        // space held turns the left button into panning, other buttons keep their tool
+        assert_eq!(
+            button_action(MouseButton::Left, true),
+            Some(ButtonAction::Pan)
+        );
+        assert_eq!(
+            button_action(MouseButton::Right, true),
+            Some(ButtonAction::Erase)
+        );
+        assert_eq!(button_action(MouseButton::Unknown, false), None);
+    }
+
+    #[test]
+    fn strip_indices_of_quads() {
+        let strip = PrimitiveMode::TriangleStrip;
+        assert_eq!(strip.quad_indices(0, 1), [0, 1, 3, 2]);
+        // the repeated indices make the triangles between quads empty
+        assert_eq!(strip.quad_indices(0, 2), [0, 1, 3, 2, 2, 4, 4, 5, 7, 6]);
+        assert_eq!(strip.quad_indices(1, 1), [2, 4, 4, 5, 7, 6]);
+    }
+
+    #[test]
+    fn fitting_view_with_and_without_margin() {
+        let (mi, ma) = (vec2(0., 0.), vec2(100., 50.));
+        let screen = vec2(400., 300.);
+        let view = View::fitting(mi, ma, screen, 0.);
+        assert_eq!(view.scale, 4.);
+        assert_eq!(view.to_screen(mi), vec2(0., 50.));
+        assert_eq!(view.to_screen(ma), vec2(400., 250.));
+        let view = View::fitting(mi, ma, screen, 20.);
+        assert!((view.scale - 3.6).abs() < 1e-5);
+        assert!((view.to_screen(mi) - vec2(20., 60.)).length() < 1e-3);
+        assert!((view.to_screen(ma) - vec2(380., 240.)).length() < 1e-3);
+        // a single point can't be zoomed into forever
+        let tiny = vec2(1e-4, 0.);
+        let view = View::fitting(mi, mi + tiny, screen, 0.);
+        assert_eq!(view.scale, MAX_ZOOM);
+        assert!((view.to_screen(mi + tiny / 2.) - screen / 2.).length() < 1e-3);
+    }
+
+    #[test]
+    fn active_path_update_keeps_the_paths_below() {
+        let update =
+            |canvas: &mut Canvas| canvas.update(1., DEFAULT_AA_WIDTH, PrimitiveMode::default());
+        let update_active = |canvas: &mut Canvas| {
+            canvas.update_active(1., DEFAULT_AA_WIDTH, PrimitiveMode::default())
+        };
+        let positions = |canvas: &Canvas| -> Vec<Vec2> {
+            canvas.vertices().iter().map(|v| v.position).collect()
+        };
+        let mut canvas = Canvas::new(0, true);
+        canvas.push_path(line(), 2.);
+        for &point in &[vec2(0., 20.), vec2(5., 20.), vec2(10., 20.)] {
+            canvas.path_mut().stroke(point);
+        }
+        update(&mut canvas);
+        assert_eq!(canvas.active_offset, Some((4, 6)));
+        canvas.path_mut().stroke(vec2(20., 20.));
+        canvas.path_mut().stroke(vec2(30., 20.));
+        update_active(&mut canvas);
+        let incremental = positions(&canvas);
+        assert_eq!(canvas.instances().unwrap().len(), 3);
+        update(&mut canvas);
+        assert_eq!(positions(&canvas), incremental);
+        assert_eq!(canvas.indices().len(), 3 * 6);
+        // the quads of the paths below are stale after these, the next update is a full one
+        let changes: [fn(&mut Canvas); 3] = [
+            |canvas| {
+                let mut view = canvas.view();
+                view.pan(vec2(5., 5.));
+                canvas.set_view(view);
+            },
+            |canvas| canvas.push_path(line(), 4.),
+            |canvas| canvas.new_path(),
+        ];
+        for change in changes.iter() {
+            update(&mut canvas);
+            assert!(canvas.active_offset.is_some());
+            change(&mut canvas);
+            assert_eq!(canvas.active_offset, None);
+            update_active(&mut canvas);
+            let rebuilt = positions(&canvas);
+            update(&mut canvas);
+            assert_eq!(rebuilt, positions(&canvas));
+        }
+    }
+}
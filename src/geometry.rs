@@ -1,21 +1,260 @@
-use glam::{vec2, Vec2};
+use glam::{vec2, Mat3, Vec2, Vec4};
+
+pub use math::{rot, wedge};
 
 #[derive(Clone, Copy, Debug)]
 #[repr(C)]
 pub struct Vertex {
     pub position: Vec2,
     pub curve: QuadCurve,
+    /// stroke width at `curve.a`, interpolated up to `end_thickness` at `curve.c`
     pub thickness: f32,
+    pub end_thickness: f32,
+    /// rgba color at `curve.a`, interpolated up to `end_color` at `curve.c`
+    pub color: Vec4,
+    pub end_color: Vec4,
+    /// `CapStyle::code` at `curve.a` and `curve.c`
+    pub caps: Vec2,
+    /// arc length of the path before `curve.a`, where the dash pattern continues from
+    pub dash_offset: f32,
+    /// dash pattern as on, off, on, off lengths, zero disables dashing
+    pub dash: Vec4,
 }
 
-fn clamp(a: f32) -> f32 {
-    if a < 0. {
-        0.
-    } else if a > 1. {
-        1.
-    } else {
-        a
+/// one curve drawn as an instance of a shared quad, the `Vertex` data that is the same for
+/// the 4 corners of its quad is stored once
+#[derive(Clone, Copy, Debug)]
+#[repr(C)]
+pub struct CurveInstance {
+    /// positions of the quad corners in `QuadCurve::oriented_bounding_box` order
+    pub corners: [Vec2; 4],
+    pub curve: QuadCurve,
+    pub thickness: f32,
+    pub end_thickness: f32,
+    pub color: Vec4,
+    pub end_color: Vec4,
+    pub caps: Vec2,
+    pub dash_offset: f32,
+    pub dash: Vec4,
+}
+
+impl CurveInstance {
+    /// instance of a curve from the 4 vertices of its quad, in the order `vertices` of paths
+    /// and curves produce them
+    pub fn from_quad(quad: &[Vertex]) -> CurveInstance {
+        let vertex = quad[0];
+        CurveInstance {
+            corners: [
+                quad[0].position,
+                quad[1].position,
+                quad[2].position,
+                quad[3].position,
+            ],
+            curve: vertex.curve,
+            thickness: vertex.thickness,
+            end_thickness: vertex.end_thickness,
+            color: vertex.color,
+            end_color: vertex.end_color,
+            caps: vertex.caps,
+            dash_offset: vertex.dash_offset,
+            dash: vertex.dash,
+        }
+    }
+}
+
+/// longest dash pattern passed to the shader
+pub const DASH_PATTERN_MAX: usize = 4;
+
+/// dash pattern in the `Vertex::dash` layout. Odd patterns are repeated twice like in svg,
+/// entries past `DASH_PATTERN_MAX` are dropped
+fn dash_vec(pattern: &[f32]) -> Vec4 {
+    let mut dash: Vec<f32> = pattern.iter().map(|len| len.max(0.)).collect();
+    if dash.len() % 2 == 1 {
+        dash.extend_from_slice(&dash.clone());
+    }
+    dash.resize(DASH_PATTERN_MAX, 0.);
+    if dash.iter().sum::<f32>() <= 0. {
+        return Vec4::zero();
     }
+    Vec4::new(dash[0], dash[1], dash[2], dash[3])
+}
+
+/// shape of the open ends of a path
+#[derive(Clone, Copy, Debug, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum CapStyle {
+    /// flat end exactly at the endpoint
+    Butt,
+    /// half disc, what the distance field gives by itself
+    #[default]
+    Round,
+    /// flat end extended past the endpoint by the stroke width
+    Square,
+}
+
+impl CapStyle {
+    /// value of the `caps` vertex attribute, joins between curves use the round code
+    fn code(self) -> f32 {
+        match self {
+            CapStyle::Round => 0.,
+            CapStyle::Butt => 1.,
+            CapStyle::Square => 2.,
+        }
+    }
+}
+
+/// how consecutive curves of a path meet
+#[derive(Clone, Copy, Debug, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum JoinStyle {
+    /// round ends of both curves overlap, what the distance field gives by itself.
+    /// Translucent strokes are blended twice where they overlap
+    #[default]
+    Overlap,
+    /// curves meet with butt ends and corners get a round join on a quad of its own,
+    /// which counts as a curve toward the buffer sizes of `Strokes`
+    Round,
+}
+
+/// how the quads curves are drawn on are assembled from triangles
+#[derive(Clone, Copy, Debug, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum PrimitiveMode {
+    /// two triangles of 6 indices per quad
+    #[default]
+    Triangles,
+    /// one triangle strip of 4 indices per quad. Quads of different curves share no
+    /// vertices, in a buffer of many of them each is joined to the previous one by 2
+    /// indices of degenerate triangles. Saves index data mostly for instanced drawing
+    /// where the single template quad needs no joins
+    TriangleStrip,
+}
+
+impl PrimitiveMode {
+    /// indices of `num` consecutive quads of 4 vertices following `first` quads already in
+    /// the buffer, vertices in the order `QuadCurve::vertices` gives
+    pub fn quad_indices(self, first: usize, num: usize) -> Vec<u32> {
+        let quads = first as u32..(first + num) as u32;
+        match self {
+            PrimitiveMode::Triangles => quads
+                .flat_map(|quad| [0, 1, 2, 0, 2, 3].iter().map(move |i| quad * 4 + i))
+                .collect(),
+            // corners go around the quad, the strip zigzags 0, 1, 3, 2. Repeating the last
+            // index of the previous quad and the first of the next one makes the triangles
+            // between them empty
+            PrimitiveMode::TriangleStrip => {
+                let mut indices = vec![];
+                for quad in quads {
+                    if quad > 0 {
+                        indices.extend_from_slice(&[4 * quad - 2, 4 * quad]);
+                    }
+                    indices.extend([0, 1, 3, 2].iter().map(|i| quad * 4 + i));
+                }
+                indices
+            }
+        }
+    }
+}
+
+/// point of a path that can be dragged to edit it, with the index of its curve
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Handle {
+    /// `a` of the curve, shared with `c` of the previous curve where they meet
+    Start(usize),
+    Control(usize),
+    /// `c` of the curve, shared with `a` of the next curve where they meet
+    End(usize),
+}
+
+/// smoothness of the join between two consecutive curves
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Continuity {
+    /// the curves don't meet
+    None,
+    /// the curves meet at a corner
+    Position,
+    /// the curves meet and leave the join in the same direction (G1)
+    Tangent,
+}
+
+/// what an input point became in `BezierPath::stroke`
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum StrokeStep {
+    /// start of the path, `last` was set
+    Start,
+    /// control point of the next curve, `control` was set
+    Control,
+    /// end of a curve that was committed to `curves`
+    Curve,
+    /// the point was closer than `min_point_distance` to `last` or `control`
+    /// and the path was left as is
+    Ignored,
+}
+
+/// default angle in radians between tangents still considered a smooth join
+pub const CONTINUITY_ANGLE_TOLERANCE: f32 = 0.01;
+/// distance between curve ends still considered a join, in path units (pixels)
+const CONTINUITY_POSITION_TOLERANCE: f32 = 1e-3;
+/// smoothed joins stay this far (as a fraction) from the control points around them,
+/// a join on a control point would have a zero tangent
+const SMOOTH_JOIN_MARGIN: f32 = 0.1;
+
+/// curves with all points closer than this are treated as points, see `QuadCurve::is_degenerate`
+pub const DEGENERATE_EPSILON: f32 = 1e-4;
+
+/// input points closer than this to the previous ones are dropped by `BezierPath::stroke`
+/// unless set otherwise, they would make curves without extent
+pub const DEFAULT_MIN_POINT_DISTANCE: f32 = 1e-3;
+
+/// rgba color of strokes unless set otherwise
+pub const DEFAULT_COLOR: Vec4 = Vec4::one();
+
+/// width in pixels of the antialiased edge of strokes unless set otherwise
+pub const DEFAULT_AA_WIDTH: f32 = 1.;
+
+/// nodes and weights of 5 point Gauss-Legendre quadrature on [-1, 1]
+const GAUSS_LEGENDRE: [(f32, f32); 5] = [
+    (0., 0.568_888_9),
+    (-0.538_469_3, 0.478_628_67),
+    (0.538_469_3, 0.478_628_67),
+    (-0.906_179_85, 0.236_926_88),
+    (0.906_179_85, 0.236_926_88),
+];
+/// relative error at which adaptive quadrature stops subdividing
+const LENGTH_TOLERANCE: f32 = 1e-5;
+const LENGTH_MAX_DEPTH: u32 = 8;
+/// bisection steps to invert arc length, enough to reach f32 precision on [0, 1]
+const LENGTH_BISECTION_STEPS: u32 = 24;
+/// relative overshoot of the path length still taken for its end, summing curve lengths
+/// in a different order than `length` rounds differently
+const PATH_END_EPSILON: f32 = 1e-5;
+/// precision of path self intersection points, in path units (pixels)
+const SELF_INTERSECTION_TOLERANCE: f32 = 1e-2;
+/// crossings this close to an endpoint shared by two curves are taken for the joint itself,
+/// subdivision reports the joint slightly off when curves meet at a shallow angle
+const SELF_INTERSECTION_JOINT_RADIUS: f32 = 10. * SELF_INTERSECTION_TOLERANCE;
+/// subdivision limit for curve-curve intersection
+const INTERSECT_MAX_DEPTH: u32 = 20;
+/// crossings collected before curve-curve intersection gives up. Two quadratic curves
+/// cross at most 4 times, the rest are near misses found twice
+const INTERSECT_MAX_CROSSINGS: usize = 16;
+/// subdivision limit for flattening, bounds output to 2^16 segments per curve
+const FLATTEN_MAX_DEPTH: u32 = 16;
+/// normals at the ends of an offset segment at most this far apart (cosine of ~25 degrees),
+/// the midpoint test alone misses symmetric bends
+const OFFSET_MIN_NORMAL_DOT: f32 = 0.9;
+/// rounds of least squares control point and newton reparameterization when fitting a curve
+const FIT_ITERATIONS: u32 = 10;
+/// samples taken along each of the curves merged by `BezierPath::join_curves`
+const JOIN_SAMPLES: u32 = 16;
+/// subdivision limit for cubic to quadratic conversion, at most 2^10 quads per cubic
+const TO_QUADS_MAX_DEPTH: u32 = 10;
+/// distance the quadratic curves of `BezierPath::from_catmull_rom` may deviate
+/// from the cubic spline
+const CATMULL_ROM_TOLERANCE: f32 = 0.05;
+
+fn clamp(a: f32) -> f32 {
+    a.clamp(0., 1.)
 }
 
 pub fn bounding_box_frame(mi: Vec2, ma: Vec2, width: f32) -> (Vec2, Vec2) {
@@ -23,16 +262,146 @@ pub fn bounding_box_frame(mi: Vec2, ma: Vec2, width: f32) -> (Vec2, Vec2) {
     (mi - frame, ma + frame)
 }
 
-/// oriented are betwee two vectors (by definition det(|v1^T \n v2^T|))
-pub fn wedge(v1: Vec2, v2: Vec2) -> f32 {
-    v1.x * v2.y - v1.y * v2.x
+/// scale and offset (`screen = world * scale + offset`) showing the box `mi`..`ma` as large
+/// as fits in a `screen` sized rectangle at the origin with `margin` left at its edges,
+/// centered and keeping the aspect ratio. An axis where the box is flat doesn't limit the
+/// scale, a box flat on both keeps scale 1
+pub fn fit_box(mi: Vec2, ma: Vec2, screen: Vec2, margin: f32) -> (f32, Vec2) {
+    let available = (screen - Vec2::splat(2. * margin)).max(Vec2::zero());
+    let size = ma - mi;
+    let fit = |available: f32, size: f32| {
+        if size > f32::EPSILON {
+            available / size
+        } else {
+            f32::INFINITY
+        }
+    };
+    let scale = fit(available.x, size.x).min(fit(available.y, size.y));
+    let scale = if scale.is_finite() { scale } else { 1. };
+    (scale, screen / 2. - (mi + ma) / 2. * scale)
+}
+
+/// how far the antialiased edge of a stroke reaches past its width, the shader fades
+/// strokes out over `aa_width` centered on the edge
+pub fn aa_margin(aa_width: f32) -> f32 {
+    aa_width / 2.
+}
+
+/// plane vector helpers the curve algorithms are written with
+pub mod math {
+    use glam::{vec2, Vec2};
+
+    /// 2d cross product, `v1.x * v2.y - v1.y * v2.x`: the signed area of the parallelogram
+    /// spanned by the vectors, positive when `v2` is counterclockwise from `v1`
+    /// (with y axis up) and zero when they are parallel
+    pub fn wedge(v1: Vec2, v2: Vec2) -> f32 {
+        v1.x * v2.y - v1.y * v2.x
+    }
+
+    /// `point` rotated around the origin by the angle with cosine `cosb` and sine `sinb`,
+    /// counterclockwise for positive angles (with y axis up). Taking them precomputed
+    /// saves the trigonometry when many points turn by the same angle, see `rotate`
+    pub fn rot(point: Vec2, cosb: f32, sinb: f32) -> Vec2 {
+        vec2(
+            cosb * point.x - sinb * point.y,
+            sinb * point.x + cosb * point.y,
+        )
+    }
+
+    /// `point` rotated around the origin by `angle` radians, same as `rot` with the
+    /// cosine and sine of `angle`
+    pub fn rotate(point: Vec2, angle: f32) -> Vec2 {
+        let (sinb, cosb) = angle.sin_cos();
+        rot(point, cosb, sinb)
+    }
+}
+
+/// distance from `p` to the segment `a`-`b`
+pub fn distance_to_segment(p: Vec2, a: Vec2, b: Vec2) -> f32 {
+    let ab = b - a;
+    let len2 = ab.length_squared();
+    if len2 <= f32::EPSILON {
+        return (p - a).length();
+    }
+    let t = clamp((p - a).dot(ab) / len2);
+    (p - (a + t * ab)).length()
+}
+
+fn boxes_overlap((mi0, ma0): (Vec2, Vec2), (mi1, ma1): (Vec2, Vec2)) -> bool {
+    mi0.x <= ma1.x && mi1.x <= ma0.x && mi0.y <= ma1.y && mi1.y <= ma0.y
+}
+
+/// real roots of `a * t^2 + b * t + c`, a double root is reported once
+fn quadratic_roots(a: f32, b: f32, c: f32) -> Vec<f32> {
+    let scale = a.abs().max(b.abs()).max(c.abs());
+    if scale == 0. {
+        return vec![];
+    }
+    if a.abs() <= f32::EPSILON * scale {
+        return if b.abs() <= f32::EPSILON * scale {
+            vec![]
+        } else {
+            vec![-c / b]
+        };
+    }
+    let disc = b * b - 4. * a * c;
+    if disc.abs() <= f32::EPSILON * b * b {
+        vec![-b / (2. * a)]
+    } else if disc < 0. {
+        vec![]
+    } else {
+        // avoids cancellation of the textbook formula
+        let q = -0.5 * (b + b.signum() * disc.sqrt());
+        if q == 0. {
+            vec![0.]
+        } else {
+            vec![q / a, c / q]
+        }
+    }
 }
 
-#[derive(Default, Debug)]
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
 pub struct BezierPath {
     pub last: Option<Vec2>,
     pub control: Option<Vec2>,
     pub curves: Vec<QuadCurve>,
+    pub closed: bool,
+    /// rgba color of every curve of the path
+    pub color: Vec4,
+    /// colors at the start and the end of the path blended along the arc length,
+    /// replaces `color` when set
+    pub gradient: Option<(Vec4, Vec4)>,
+    /// ends of the path when it's not closed
+    pub cap: CapStyle,
+    /// joins between the curves
+    pub join: JoinStyle,
+    /// on and off lengths of dashes along the path, solid if `None`
+    pub dash_pattern: Option<Vec<f32>>,
+    /// `stroke` ignores points closer than this to `last` or `control`
+    pub min_point_distance: f32,
+    /// curves removed by `undo`, most recent last
+    #[cfg_attr(feature = "serde", serde(skip))]
+    redo: Vec<QuadCurve>,
+}
+
+impl Default for BezierPath {
+    fn default() -> BezierPath {
+        BezierPath {
+            last: None,
+            control: None,
+            curves: vec![],
+            closed: false,
+            color: DEFAULT_COLOR,
+            gradient: None,
+            cap: CapStyle::default(),
+            join: JoinStyle::default(),
+            dash_pattern: None,
+            min_point_distance: DEFAULT_MIN_POINT_DISTANCE,
+            redo: vec![],
+        }
+    }
 }
 
 impl BezierPath {
@@ -40,9 +409,42 @@ impl BezierPath {
         self.last = None;
         self.control = None;
         self.curves = vec![];
+        self.closed = false;
+        self.redo.clear();
+    }
+
+    /// connects the end of the path back to the start of the first curve. The pending
+    /// control point is used for the closing curve if there is one, otherwise it is a
+    /// straight segment. Does nothing for paths with fewer than two curves
+    pub fn close(&mut self) {
+        if self.closed || self.curves.len() < 2 {
+            return;
+        }
+        let start = self.curves[0].a;
+        if let Some(last) = self.last {
+            if last != start {
+                let control = self.control.unwrap_or_else(|| (last + start) / 2.);
+                self.curves.push(QuadCurve::new(last, control, start));
+                self.redo.clear();
+            }
+        }
+        self.last = Some(start);
+        self.control = None;
+        self.closed = true;
     }
 
-    pub fn stroke(&mut self, point: Vec2) {
+    /// feeds the next input point, every curve takes a control point and an end point after
+    /// the start. Returns which of them the point became. A point repeating `last` or
+    /// `control` within `min_point_distance` is ignored, clicking the same spot twice
+    /// would give a curve without extent
+    pub fn stroke(&mut self, point: Vec2) -> StrokeStep {
+        let repeated = |previous: Option<Vec2>| {
+            previous.is_some_and(|p| (point - p).length() < self.min_point_distance)
+        };
+        if repeated(self.last) || repeated(self.control) {
+            return StrokeStep::Ignored;
+        }
+        self.redo.clear();
         if let (Some(last), Some(control)) = (self.last, self.control) {
             self.curves.push(QuadCurve {
                 a: last,
@@ -51,152 +453,3070 @@ impl BezierPath {
             });
             self.last = Some(point);
             self.control = None;
+            StrokeStep::Curve
         } else if self.last.is_none() {
             self.last = Some(point);
-        } else if self.control.is_none() {
+            StrokeStep::Start
+        } else {
             self.control = Some(point);
-        };
+            StrokeStep::Control
+        }
+    }
+
+    /// curve the next point at `cursor` would commit, `None` unless both the start and the
+    /// control point of it are set. The path is left as is
+    pub fn preview_curve(&self, cursor: Vec2) -> Option<QuadCurve> {
+        match (self.last, self.control) {
+            (Some(last), Some(control)) => Some(QuadCurve::new(last, control, cursor)),
+            _ => None,
+        }
     }
 
+    /// removes the last curve and makes its start and control point pending again, so the
+    /// next point replaces its end. A pending control point is dropped. Does nothing on a
+    /// path without curves
     pub fn undo(&mut self) {
         if let Some(curve) = self.curves.pop() {
+            self.closed = false;
             self.last = Some(curve.a);
             self.control = Some(curve.control);
+            self.redo.push(curve);
         }
     }
 
-    pub fn vertices(&self, width: f32) -> (Vec<Vertex>, Vec<u16>) {
-        let mut vertices = vec![];
-        let mut indices = vec![];
+    /// restores the curve removed by the last `undo`, the path then waits for the
+    /// control point of the next curve
+    pub fn redo(&mut self) {
+        if let Some(curve) = self.redo.pop() {
+            self.last = Some(curve.c);
+            self.control = None;
+            self.curves.push(curve);
+        }
+    }
+
+    /// total arc length of all curves
+    pub fn length(&self) -> f32 {
+        self.curves.iter().map(QuadCurve::length).sum()
+    }
+
+    /// signed area the curves enclose, positive for counterclockwise paths (with y axis up).
+    /// The path is treated as closed, a gap between the end and the start is bridged
+    /// by a straight line. Parts winding around twice count twice
+    pub fn enclosed_area(&self) -> f32 {
+        let (first, last) = match (self.curves.first(), self.curves.last()) {
+            (Some(first), Some(last)) => (first, last),
+            _ => return 0.,
+        };
+        self.curves
+            .iter()
+            .map(QuadCurve::signed_area_term)
+            .sum::<f32>()
+            + wedge(last.c, first.a) / 2.
+    }
+
+    /// center of mass of the area the curves enclose, closed the same way as for
+    /// `enclosed_area`. `None` for paths neither closed nor ending where they start
+    /// and for ones enclosing no area
+    pub fn centroid(&self) -> Option<Vec2> {
+        let (first, last) = (self.curves.first()?, self.curves.last()?);
+        if !self.closed && (last.c - first.a).length() > CONTINUITY_POSITION_TOLERANCE {
+            return None;
+        }
+        let area = self.enclosed_area();
+        if area.abs() <= f32::EPSILON {
+            return None;
+        }
+        let closing = QuadCurve::new(last.c, (last.c + first.a) / 2., first.a);
+        let moment = self
+            .curves
+            .iter()
+            .chain(std::iter::once(&closing))
+            .map(QuadCurve::first_moment_term)
+            .fold(Vec2::zero(), |sum, term| sum + term);
+        Some(moment / area)
+    }
+
+    /// point at arc length `dist` from the start of the path, `None` if `dist` is
+    /// negative or past the end. `length()` itself gives the end of the last curve
+    pub fn point_at_distance(&self, dist: f32) -> Option<Vec2> {
+        if dist < 0. {
+            return None;
+        }
+        let mut rest = dist;
         for curve in self.curves.iter() {
-            let (vrts, ids) = curve.vertices(width);
-            let indices_size = vertices.len() as u16;
-            vertices.extend(vrts);
-            for i in ids.iter() {
-                indices.push(indices_size + i);
+            let length = curve.length();
+            if rest <= length {
+                return Some(curve.point_at(curve.t_at_length(rest)));
             }
+            rest -= length;
+        }
+        // rounding of the subtractions may overshoot the last curve by a hair
+        match self.curves.last() {
+            Some(last) if rest <= PATH_END_EPSILON * dist => Some(last.c),
+            _ => None,
         }
-        (vertices, indices)
     }
-}
 
-pub fn rot(point: Vec2, cosb: f32, sinb: f32) -> Vec2 {
-    vec2(
-        cosb * point.x - sinb * point.y,
-        sinb * point.x + cosb * point.y,
-    )
-}
+    /// index of the curve at arc length `dist` from the start of the path and the parameter
+    /// on it. Distances before the start or past the end are clamped to them,
+    /// `None` only for paths without curves
+    pub fn param_at_distance(&self, dist: f32) -> Option<(usize, f32)> {
+        let last = self.curves.len().checked_sub(1)?;
+        if dist <= 0. {
+            return Some((0, 0.));
+        }
+        let mut rest = dist;
+        for (i, curve) in self.curves.iter().enumerate() {
+            let length = curve.length();
+            if rest <= length {
+                return Some((i, curve.t_at_length(rest)));
+            }
+            rest -= length;
+        }
+        Some((last, 1.))
+    }
 
-#[derive(Copy, Clone, Debug, PartialEq)]
-#[repr(C)]
-pub struct QuadCurve {
-    pub a: Vec2,
-    pub control: Vec2,
-    pub c: Vec2,
-}
+    /// index and parameter of the curve closest to `p`, `None` if every curve is farther
+    /// than `max_dist`. Where curves overlap the first one wins
+    pub fn hit_test(&self, p: Vec2, max_dist: f32) -> Option<(usize, f32)> {
+        let mut best: Option<(usize, f32, f32)> = None;
+        for (i, curve) in self.curves.iter().enumerate() {
+            let (mi, ma) = curve.bounding_box();
+            let (mi, ma) = bounding_box_frame(mi, ma, max_dist);
+            if p.x < mi.x || p.y < mi.y || p.x > ma.x || p.y > ma.y {
+                continue;
+            }
+            let (t, point) = curve.closest_point(p);
+            let dist = (point - p).length();
+            if dist <= max_dist && best.is_none_or(|(_, _, best_dist)| dist < best_dist) {
+                best = Some((i, t, dist));
+            }
+        }
+        best.map(|(i, t, _)| (i, t))
+    }
 
-impl QuadCurve {
-    pub fn new(a: Vec2, control: Vec2, c: Vec2) -> QuadCurve {
-        QuadCurve { a, control, c }
+    /// arc length from the start of the path to the point of the path nearest to `p`,
+    /// and that point. `None` for an empty path
+    pub fn nearest_on_path(&self, p: Vec2) -> Option<(f32, Vec2)> {
+        let (index, t) = self.hit_test(p, f32::INFINITY)?;
+        let curve = &self.curves[index];
+        let before: f32 = self.curves[..index].iter().map(QuadCurve::length).sum();
+        Some((before + curve.length_to(t), curve.point_at(t)))
     }
 
-    pub fn vertices(&self, width: f32) -> (Vec<Vertex>, Vec<u16>) {
-        let (a, b, c, d) = self.optimal_bb(width);
-        let indices = vec![0, 1, 2, 0, 2, 3];
-        (
-            vec![
-                Vertex {
-                    position: a,
-                    curve: self.clone(),
-                    thickness: width,
-                },
-                Vertex {
-                    position: b,
-                    curve: self.clone(),
-                    thickness: width,
-                },
-                Vertex {
-                    position: c,
-                    curve: self.clone(),
-                    thickness: width,
-                },
-                Vertex {
-                    position: d,
-                    curve: self.clone(),
-                    thickness: width,
-                },
-            ],
-            indices,
-        )
+    /// the handle nearest to `p` within `radius`. Where handles coincide the first curve's
+    /// one wins
+    pub fn handle_at(&self, p: Vec2, radius: f32) -> Option<Handle> {
+        let mut best: Option<(Handle, f32)> = None;
+        for (i, curve) in self.curves.iter().enumerate() {
+            let handles = [
+                (Handle::Start(i), curve.a),
+                (Handle::Control(i), curve.control),
+                (Handle::End(i), curve.c),
+            ];
+            for (handle, point) in handles.iter() {
+                let dist = (*point - p).length();
+                if dist <= radius && best.is_none_or(|(_, best_dist)| dist < best_dist) {
+                    best = Some((*handle, dist));
+                }
+            }
+        }
+        best.map(|(handle, _)| handle)
     }
-    /// basically https://www.iquilezles.org/www/articles/bezierbbox/bezierbbox.htm
-    /// with extra rotation
-    fn optimal_bb(&self, width: f32) -> (Vec2, Vec2, Vec2, Vec2) {
-        let dir = self.c - self.a;
-        let ndir = dir.normalize();
-        let ox = vec2(1., 0.);
-        let sinb = wedge(ndir, ox);
-        let cosb = (self.c - self.a).normalize().dot(ox);
-        // align with Ox axis
-        let p0 = self.a;
-        let p0p1 = self.control - self.a;
-        let p1 = p0 + rot(p0p1, cosb, sinb);
-        let p2 = p0 + vec2(dir.length(), 0.);
 
-        // calculation inflection point in this coordinates
-        let mut mi = p0.min(p2);
-        let mut ma = p0.max(p2);
-        if p1.x < mi.x || p1.x > ma.x || p1.y < mi.y || p1.y > ma.y {
-            let t = (p0 - p1) / (p0 - 2. * p1 + p2);
-            let t = vec2(clamp(t.x), clamp(t.y));
-            let s = vec2(1., 1.) - t;
-            let q = s * s * p0 + 2.0 * s * t * p1 + t * t * p2;
-            mi = mi.min(q);
-            ma = ma.max(q);
+    /// moves the point of `handle` to `to`. Ends move together with the curve end they
+    /// are joined to (including the closing join and the pending end of the path), so the
+    /// path stays connected. Handles of missing curves are ignored
+    pub fn move_handle(&mut self, handle: Handle, to: Vec2) {
+        let count = self.curves.len();
+        let joined = |i: usize, next: bool| {
+            if next && i + 1 < count {
+                Some(i + 1)
+            } else if !next && i > 0 {
+                Some(i - 1)
+            } else if self.closed && count > 1 {
+                Some(if next { 0 } else { count - 1 })
+            } else {
+                None
+            }
+        };
+        match handle {
+            Handle::Control(i) if i < count => self.curves[i].control = to,
+            Handle::Start(i) if i < count => {
+                let from = self.curves[i].a;
+                if let Some(prev) = joined(i, false).filter(|&j| self.curves[j].c == from) {
+                    self.curves[prev].c = to;
+                }
+                if self.last == Some(from) {
+                    self.last = Some(to);
+                }
+                self.curves[i].a = to;
+            }
+            Handle::End(i) if i < count => {
+                let from = self.curves[i].c;
+                if let Some(next) = joined(i, true).filter(|&j| self.curves[j].a == from) {
+                    self.curves[next].a = to;
+                }
+                if self.last == Some(from) {
+                    self.last = Some(to);
+                }
+                self.curves[i].c = to;
+            }
+            _ => {}
         }
-        let (mi, ma) = bounding_box_frame(mi, ma, width);
-        // now align back with bezier
-        let ma_rotated = p0 + rot(ma - p0, cosb, -sinb);
-        let mi_rotated = p0 + rot(mi - p0, cosb, -sinb);
-        let offset = ndir.dot(mi_rotated - ma_rotated) * ndir;
-        let b = ma_rotated + offset;
-        let d = mi_rotated - offset;
-        (mi_rotated, b, ma_rotated, d)
     }
 
-    /// bounding box with edges parallel to Ox Oy
-    pub fn bounding_box(&self) -> (Vec2, Vec2) {
-        let p0 = self.a;
-        let p1 = self.control;
-        let p2 = self.c;
-        let mut mi = p0.min(p2);
-        let mut ma = p0.max(p2);
-        if p1.x < mi.x || p1.x > ma.x || p1.y < mi.y || p1.y > ma.y {
-            let t = (p0 - p1) / (p0 - 2. * p1 + p2);
-            let t = vec2(clamp(t.x), clamp(t.y));
-            let s = vec2(1., 1.) - t;
-            let q = s * s * p0 + 2.0 * s * t * p1 + t * t * p2;
-            mi = mi.min(q);
-            ma = ma.max(q);
+    /// union of the curves' axis aligned bounding boxes, `None` for an empty path
+    pub fn bounding_box(&self) -> Option<(Vec2, Vec2)> {
+        self.curves
+            .iter()
+            .map(QuadCurve::bounding_box)
+            .fold(None, |acc, (mi, ma)| match acc {
+                Some((acc_mi, acc_ma)) => Some((mi.min(acc_mi), ma.max(acc_ma))),
+                None => Some((mi, ma)),
+            })
+    }
+
+    /// transform for "zoom to fit": the `bounding_box` of the path mapped into a `screen`
+    /// sized rectangle at the origin with `margin` around it, see `fit_box`.
+    /// Identity for an empty path
+    pub fn fit_transform(&self, screen: Vec2, margin: f32) -> Mat3 {
+        match self.bounding_box() {
+            Some((mi, ma)) => {
+                let (scale, offset) = fit_box(mi, ma, screen, margin);
+                Mat3::from_scale_angle_translation(Vec2::splat(scale), 0., offset)
+            }
+            None => Mat3::identity(),
         }
-        (mi, ma)
     }
 
-    pub fn scale(&self, scale: f32) -> QuadCurve {
-        QuadCurve {
-            a: self.a * scale,
-            control: self.control * scale,
-            c: self.c * scale,
+    /// crossings between curves of the path as pairs of curve indices `i < j` and
+    /// the crossing point. Touching at an endpoint shared by both curves (as consecutive
+    /// curves do) doesn't count. Where curves run along each other, as when the path
+    /// doubles back, the ends of the shared stretch are reported instead, joints included
+    pub fn self_intersections(&self) -> Vec<(usize, usize, Vec2)> {
+        let mut result = vec![];
+        for (i, first) in self.curves.iter().enumerate() {
+            for (j, second) in self.curves.iter().enumerate().skip(i + 1) {
+                let joints: Vec<Vec2> = [(first.a, second.c), (first.c, second.a)]
+                    .iter()
+                    .chain([(first.a, second.a), (first.c, second.c)].iter())
+                    .filter(|(p, q)| (*p - *q).length() < SELF_INTERSECTION_TOLERANCE)
+                    .map(|(p, _)| *p)
+                    .collect();
+                let (crossings, overlap_ends) =
+                    first.crossings_and_overlap_ends(second, SELF_INTERSECTION_TOLERANCE);
+                for point in crossings {
+                    let at_joint = joints
+                        .iter()
+                        .any(|joint| (*joint - point).length() < SELF_INTERSECTION_JOINT_RADIUS);
+                    if !at_joint {
+                        result.push((i, j, point));
+                    }
+                }
+                result.extend(overlap_ends.into_iter().map(|point| (i, j, point)));
+            }
         }
+        result
     }
 
-    pub fn split(&self) -> (QuadCurve, QuadCurve) {
-        let q0 = (self.a + self.control) / 2.;
-        let q1 = (self.control + self.c) / 2.;
-        let r0 = (q0 + q1) / 2.;
-        (
-            QuadCurve::new(self.a, q0, r0),
-            QuadCurve::new(r0, q1, self.c),
-        )
+    /// `(curve index, t, point)` at `samples_per_curve` evenly spaced parameters of every
+    /// curve, both ends included. Shared endpoints of consecutive curves are yielded twice
+    pub fn iter_samples(
+        &self,
+        samples_per_curve: usize,
+    ) -> impl Iterator<Item = (usize, f32, Vec2)> + '_ {
+        let steps = samples_per_curve.saturating_sub(1).max(1) as f32;
+        self.curves.iter().enumerate().flat_map(move |(i, curve)| {
+            (0..samples_per_curve).map(move |k| {
+                let t = k as f32 / steps;
+                (i, t, curve.point_at(t))
+            })
+        })
+    }
+
+    /// smoothness of every join between consecutive curves, and of the closing join from the
+    /// last curve to the first one for closed paths. See `continuity_within` for the tolerance
+    pub fn continuity(&self) -> Vec<Continuity> {
+        self.continuity_within(CONTINUITY_ANGLE_TOLERANCE)
+    }
+
+    /// `continuity` with joins counted as smooth when tangents differ by at most
+    /// `angle_tolerance` radians
+    pub fn continuity_within(&self, angle_tolerance: f32) -> Vec<Continuity> {
+        let mut joins: Vec<_> = self
+            .curves
+            .windows(2)
+            .map(|pair| (pair[0], pair[1]))
+            .collect();
+        if self.closed && self.curves.len() > 1 {
+            joins.push((self.curves[self.curves.len() - 1], self.curves[0]));
+        }
+        joins
+            .into_iter()
+            .map(|(incoming, outgoing)| {
+                if (incoming.c - outgoing.a).length() > CONTINUITY_POSITION_TOLERANCE {
+                    return Continuity::None;
+                }
+                let (d0, d1) = (incoming.end_direction(), outgoing.start_direction());
+                if wedge(d0, d1).atan2(d0.dot(d1)).abs() <= angle_tolerance {
+                    Continuity::Tangent
+                } else {
+                    Continuity::Position
+                }
+            })
+            .collect()
+    }
+
+    /// moves the joins toward the segment between the control points around them, with
+    /// `strength` 1 every join is on that segment and the path is G1 (`Continuity::Tangent`).
+    /// A quadratic curve has a single control point shaping both of its ends, so joins are
+    /// moved rather than controls. Joins where curves don't meet and open ends stay in place
+    pub fn smooth(&mut self, strength: f32) {
+        let strength = clamp(strength);
+        let count = self.curves.len();
+        let joins = if self.closed {
+            count
+        } else {
+            count.saturating_sub(1)
+        };
+        for i in 0..joins {
+            let next = (i + 1) % count;
+            let (incoming, outgoing) = (self.curves[i], self.curves[next]);
+            if (incoming.c - outgoing.a).length() > CONTINUITY_POSITION_TOLERANCE {
+                continue;
+            }
+            let (c0, c1) = (incoming.control, outgoing.control);
+            let span = c1 - c0;
+            if span.length_squared() <= f32::EPSILON {
+                continue;
+            }
+            let t = ((incoming.c - c0).dot(span) / span.dot(span))
+                .clamp(SMOOTH_JOIN_MARGIN, 1. - SMOOTH_JOIN_MARGIN);
+            let join = incoming.c.lerp(c0 + span * t, strength);
+            if self.last == Some(outgoing.a) {
+                self.last = Some(join);
+            }
+            self.curves[i].c = join;
+            self.curves[next].a = join;
+        }
+    }
+
+    /// puts `curve` before the curve at `index`, or at the end if `index` equals the number
+    /// of curves. Curves are not moved to fit, neighbours keep their endpoints. Inserting
+    /// at the end continues the path from the end of `curve`
+    ///
+    /// # Panics
+    ///
+    /// if `index` is greater than the number of curves
+    pub fn insert_curve(&mut self, index: usize, curve: QuadCurve) {
+        self.redo.clear();
+        self.curves.insert(index, curve);
+        if index + 1 == self.curves.len() {
+            self.last = Some(curve.c);
+            self.control = None;
+        }
+    }
+
+    /// replaces the curve at `index` by its two halves split at `t`, so a new point can be
+    /// edited there without changing the outline. False if there is no such curve or `t`
+    /// isn't strictly inside (0, 1), a half would collapse into a point
+    pub fn split_curve(&mut self, index: usize, t: f32) -> bool {
+        if index >= self.curves.len() || t <= 0. || t >= 1. {
+            return false;
+        }
+        self.redo.clear();
+        let (left, right) = self.curves[index].split_at(t);
+        self.curves[index] = left;
+        self.curves.insert(index + 1, right);
+        true
+    }
+
+    /// merges the curves at `index` and `index + 1` into one keeping their outer ends, the
+    /// control point is fitted through samples of both. Two quadratic curves rarely make
+    /// up a quadratic one, returns how far the samples are from the merged curve.
+    /// `None` if there is no curve after `index`
+    pub fn join_curves(&mut self, index: usize) -> Option<f32> {
+        self.join_curves_within(index, f32::INFINITY)
+    }
+
+    /// `join_curves` that keeps the curves as they are when the merged curve would be
+    /// farther than `tolerance` from them. Returns the distance either way
+    pub fn join_curves_within(&mut self, index: usize, tolerance: f32) -> Option<f32> {
+        if index + 1 >= self.curves.len() {
+            return None;
+        }
+        let (first, second) = (self.curves[index], self.curves[index + 1]);
+        let mut points: Vec<Vec2> = (0..JOIN_SAMPLES)
+            .map(|i| first.point_at(i as f32 / JOIN_SAMPLES as f32))
+            .chain((0..=JOIN_SAMPLES).map(|i| second.point_at(i as f32 / JOIN_SAMPLES as f32)))
+            .collect();
+        points.dedup();
+        let merged = if points.len() >= 3 {
+            fit_curve(&points).0
+        } else {
+            QuadCurve::new(first.a, first.a.lerp(second.c, 0.5), second.c)
+        };
+        let error = points
+            .iter()
+            .map(|&point| merged.distance(point))
+            .fold(0., f32::max);
+        if error <= tolerance {
+            self.redo.clear();
+            self.curves[index] = merged;
+            self.curves.remove(index + 1);
+        }
+        Some(error)
+    }
+
+    /// takes out the curve at `index`, `None` if there is no such curve. Removing from the
+    /// middle leaves a gap between the ends of its neighbours, use `insert_curve` to bridge
+    /// it. Removing the last curve continues the path from the start of the removed one
+    pub fn remove_curve(&mut self, index: usize) -> Option<QuadCurve> {
+        if index >= self.curves.len() {
+            return None;
+        }
+        self.redo.clear();
+        let curve = self.curves.remove(index);
+        if index == self.curves.len() {
+            self.last = Some(curve.a);
+            self.control = None;
+        }
+        Some(curve)
+    }
+
+    /// adds the curves of `other` after the curves of this path. With `connect` a straight
+    /// curve bridges the end of this path and the start of `other` when they don't meet.
+    /// Pending points come from `other` unless it has none, the result is closed only if this path was empty
+    /// and `other` is closed
+    pub fn append(&mut self, other: &BezierPath, connect: bool) {
+        self.redo.clear();
+        self.closed = self.curves.is_empty() && other.closed;
+        if let (true, Some(end), Some(start)) = (
+            connect,
+            self.curves.last().map(|curve| curve.c),
+            other.curves.first().map(|curve| curve.a),
+        ) {
+            if end != start {
+                self.curves
+                    .push(QuadCurve::new(end, (end + start) / 2., start));
+            }
+        }
+        self.curves.extend_from_slice(&other.curves);
+        if other.last.is_some() {
+            self.last = other.last;
+            self.control = other.control;
+        }
+    }
+
+    /// flips the direction of the path, it then ends where it used to start.
+    /// The pending control point and the redo history belong to the old end and are dropped
+    pub fn reverse(&mut self) {
+        self.curves.reverse();
+        for curve in self.curves.iter_mut() {
+            *curve = curve.reverse();
+        }
+        if let Some(curve) = self.curves.last() {
+            self.last = Some(curve.c);
+        }
+        self.control = None;
+        self.redo.clear();
+    }
+
+    /// applies 2d affine transform `m` to every curve and the pending points
+    pub fn transform(&mut self, m: Mat3) {
+        for curve in self.curves.iter_mut().chain(self.redo.iter_mut()) {
+            *curve = curve.transform(m);
+        }
+        self.last = self.last.map(|p| m.transform_point2(p));
+        self.control = self.control.map(|p| m.transform_point2(p));
+    }
+
+    pub fn vertices(&self, width: f32) -> (Vec<Vertex>, Vec<u32>) {
+        self.vertices_tapered(width, width)
+    }
+
+    /// vertices with width changing linearly along the arc length of the whole path
+    /// from `start_width` to `end_width`
+    pub fn vertices_tapered(&self, start_width: f32, end_width: f32) -> (Vec<Vertex>, Vec<u32>) {
+        self.vertices_antialiased(start_width, end_width, DEFAULT_AA_WIDTH)
+    }
+
+    /// same as `vertices_tapered` with quads padded to fit the antialiased edge
+    /// drawn with `aa_width`
+    pub fn vertices_antialiased(
+        &self,
+        start_width: f32,
+        end_width: f32,
+        aa_width: f32,
+    ) -> (Vec<Vertex>, Vec<u32>) {
+        self.tessellate(&Tessellation::new(self, start_width, end_width, aa_width))
+    }
+
+    /// same as `vertices_antialiased` with a width for every point where curves meet,
+    /// e.g. from stylus pressure: `widths[i]` at the start of curve `i` and `widths[i + 1]`
+    /// at its end, each curve tapers linearly between the two. The last width is repeated
+    /// when fewer than one more than the curves are given
+    pub fn vertices_with_widths(&self, widths: &[f32], aa_width: f32) -> (Vec<Vertex>, Vec<u32>) {
+        let mut tessellation = Tessellation::new(self, 0., 0., aa_width);
+        tessellation.widths = Some(widths);
+        self.tessellate(&tessellation)
+    }
+
+    fn tessellate(&self, tessellation: &Tessellation) -> (Vec<Vertex>, Vec<u32>) {
+        let mut dist = 0.;
+        let mut vertices = vec![];
+        for (i, curve) in self.curves.iter().enumerate() {
+            let before = dist;
+            if tessellation.measured {
+                dist += curve.length();
+            }
+            vertices.extend(tessellation.quads(i, before, dist));
+        }
+        let indices = PrimitiveMode::Triangles.quad_indices(0, vertices.len() / 4);
+        (vertices, indices)
+    }
+
+    /// same as `vertices` with curves tessellated on the rayon thread pool,
+    /// the output is identical
+    #[cfg(feature = "rayon")]
+    pub fn vertices_parallel(&self, width: f32) -> (Vec<Vertex>, Vec<u32>) {
+        use rayon::prelude::*;
+
+        let tessellation = Tessellation::new(self, width, width, DEFAULT_AA_WIDTH);
+        // arc length before every curve, summed in order so it matches the serial one
+        let mut dists = vec![0.; self.curves.len() + 1];
+        if tessellation.measured {
+            let lengths: Vec<f32> = self.curves.par_iter().map(QuadCurve::length).collect();
+            for (i, length) in lengths.into_iter().enumerate() {
+                dists[i + 1] = dists[i] + length;
+            }
+        }
+        let quads: Vec<Vec<Vertex>> = (0..self.curves.len())
+            .into_par_iter()
+            .map(|i| tessellation.quads(i, dists[i], dists[i + 1]))
+            .collect();
+        let vertices: Vec<Vertex> = quads.concat();
+        let indices = PrimitiveMode::Triangles.quad_indices(0, vertices.len() / 4);
+        (vertices, indices)
+    }
+
+    /// replaces the curves by fewer ones within about `tolerance` of the original:
+    /// the path is flattened, reduced with ramer-douglas-peucker and the remaining points
+    /// are fitted again, each step takes a third of the tolerance.
+    /// The start and the end of the path are kept exactly
+    pub fn simplify(&mut self, tolerance: f32) {
+        if self.curves.is_empty() {
+            return;
+        }
+        let step = tolerance / 3.;
+        let mut points = vec![];
+        for curve in self.curves.iter() {
+            points.extend(curve.flatten(step).into_iter().skip(1));
+        }
+        points.insert(0, self.curves[0].a);
+        points.dedup();
+        if points.len() < 2 {
+            return;
+        }
+        let mut simplified = vec![];
+        douglas_peucker(&points, step, &mut simplified);
+        self.redo.clear();
+        self.curves = BezierPath::fit(&simplified, step).curves;
+    }
+
+    /// quadratic curves approximating `points`, every point is within `error` of the
+    /// curve fitted to it. Schneider style: a curve is fitted by least squares over
+    /// chord length parameters refined with newton steps, and the points are split at the
+    /// worst one while the error is exceeded. Curves meet at input points but tangents
+    /// are not matched across joints
+    pub fn fit(points: &[Vec2], error: f32) -> BezierPath {
+        let mut points = points.to_vec();
+        points.dedup();
+        let mut path = BezierPath::default();
+        if points.len() >= 2 {
+            fit_into(&points, error, &mut path.curves);
+        }
+        path.last = points.last().copied();
+        path
+    }
+
+    /// curves passing through every one of `points`, a cardinal spline: the tangent at
+    /// a point is `(1 - tension)` times half the difference of its neighbours, so `tension`
+    /// 0 is catmull-rom and 1 gives straight segments. The ends repeat the first and the
+    /// last point. Each cubic segment is approximated by quadratic curves within
+    /// `CATMULL_ROM_TOLERANCE`, they meet the points exactly
+    pub fn from_catmull_rom(points: &[Vec2], tension: f32) -> BezierPath {
+        let mut points = points.to_vec();
+        points.dedup();
+        let mut path = BezierPath::default();
+        let n = points.len();
+        let tangent = |i: usize| {
+            let prev = points[i.saturating_sub(1)];
+            let next = points[(i + 1).min(n - 1)];
+            (1. - tension) * (next - prev) / 2.
+        };
+        for i in 1..n {
+            let (a, b) = (points[i - 1], points[i]);
+            let cubic = CubicCurve::new(a, a + tangent(i - 1) / 3., b - tangent(i) / 3., b);
+            path.curves.extend(cubic.to_quads(CATMULL_ROM_TOLERANCE));
+        }
+        path.last = points.last().copied();
+        path
+    }
+}
+
+/// style of a path resolved for building its vertices one curve at a time
+struct Tessellation<'a> {
+    path: &'a BezierPath,
+    start_width: f32,
+    end_width: f32,
+    /// width at every joint of the curves, overrides `start_width` and `end_width`
+    widths: Option<&'a [f32]>,
+    aa_width: f32,
+    dash: Vec4,
+    /// arc lengths are needed for tapering, gradients or dashes
+    measured: bool,
+    /// length of the whole path when widths or colors are blended along it, 0 otherwise
+    total: f32,
+    /// join after every curve, the closing one included
+    joins: Vec<Continuity>,
+}
+
+impl<'a> Tessellation<'a> {
+    fn new(
+        path: &'a BezierPath,
+        start_width: f32,
+        end_width: f32,
+        aa_width: f32,
+    ) -> Tessellation<'a> {
+        let dash = path
+            .dash_pattern
+            .as_ref()
+            .map_or(Vec4::zero(), |pattern| dash_vec(pattern));
+        // lengths are skipped when possible, this runs on every mouse move
+        let blended = start_width != end_width || path.gradient.is_some();
+        let joins = match path.join {
+            JoinStyle::Overlap => vec![],
+            JoinStyle::Round => path.continuity(),
+        };
+        Tessellation {
+            path,
+            start_width,
+            end_width,
+            widths: None,
+            aa_width,
+            dash,
+            measured: blended || dash != Vec4::zero(),
+            total: if blended { path.length() } else { 0. },
+            joins,
+        }
+    }
+
+    fn fraction(&self, dist: f32) -> f32 {
+        if self.total > 0. {
+            dist / self.total
+        } else {
+            0.
+        }
+    }
+
+    fn width_at(&self, dist: f32) -> f32 {
+        self.start_width + (self.end_width - self.start_width) * self.fraction(dist)
+    }
+
+    /// widths at the start and the end of the curve at `index`
+    /// lying between arc lengths `before` and `after`
+    fn widths_of(&self, index: usize, before: f32, after: f32) -> (f32, f32) {
+        match self.widths {
+            Some(widths) => {
+                let joint = |i: usize| widths.get(i).or(widths.last()).copied().unwrap_or(0.);
+                (joint(index), joint(index + 1))
+            }
+            None => (self.width_at(before), self.width_at(after)),
+        }
+    }
+
+    fn color_at(&self, dist: f32) -> Vec4 {
+        match self.path.gradient {
+            Some((start, end)) => start.lerp(end, self.fraction(dist)),
+            None => self.path.color,
+        }
+    }
+
+    /// quads of the curve at `index` lying between arc lengths `before` and `after`
+    /// along the path: the curve itself and its join if it needs one
+    fn quads(&self, index: usize, before: f32, after: f32) -> Vec<Vertex> {
+        let path = self.path;
+        let curve = path.curves[index];
+        let n = path.curves.len();
+        let (start, end) = self.widths_of(index, before, after);
+        let (start_color, end_color) = (self.color_at(before), self.color_at(after));
+        let cap_at = |end_of_path, join: Option<&Continuity>| match join {
+            _ if end_of_path && !path.closed => path.cap,
+            Some(Continuity::Position) | Some(Continuity::Tangent) => CapStyle::Butt,
+            _ => CapStyle::Round,
+        };
+        let join_after = self.joins.get(index);
+        let start_cap = cap_at(index == 0, self.joins.get((index + n - 1) % n));
+        let end_cap = cap_at(index == n - 1, join_after);
+        // corners of a square cap stick out of the box padded by the width
+        let pad = if start_cap == CapStyle::Square || end_cap == CapStyle::Square {
+            start.max(end) * std::f32::consts::SQRT_2
+        } else {
+            start.max(end)
+        } + aa_margin(self.aa_width);
+        let (vertices, _) = curve.padded_vertices(start, end, pad);
+        let mut quads: Vec<Vertex> = vertices
+            .into_iter()
+            .map(|vertex| Vertex {
+                color: start_color,
+                end_color,
+                caps: vec2(start_cap.code(), end_cap.code()),
+                dash_offset: before,
+                dash: self.dash,
+                ..vertex
+            })
+            .collect();
+        // butt ends leave a notch on the outer side of a corner, a point curve covers it
+        if join_after == Some(&Continuity::Position) {
+            let point = QuadCurve::new(curve.c, curve.c, curve.c);
+            let (vertices, _) = point.padded_vertices(end, end, end + aa_margin(self.aa_width));
+            quads.extend(vertices.into_iter().map(|vertex| Vertex {
+                color: end_color,
+                end_color,
+                dash_offset: after,
+                dash: self.dash,
+                ..vertex
+            }));
+        }
+        quads
+    }
+}
+
+/// ramer-douglas-peucker, keeps the points farther than `tolerance` from the chord of their
+/// neighbours, first and last points always stay. Kept points are appended to `simplified`,
+/// `points` needs at least two
+pub fn douglas_peucker(points: &[Vec2], tolerance: f32, simplified: &mut Vec<Vec2>) {
+    let (first, last) = (points[0], points[points.len() - 1]);
+    let farthest = points[1..points.len() - 1]
+        .iter()
+        .map(|&point| distance_to_segment(point, first, last))
+        .enumerate()
+        .fold(
+            None,
+            |farthest: Option<(usize, f32)>, (i, dist)| match farthest {
+                Some((_, max)) if max >= dist => farthest,
+                _ => Some((i + 1, dist)),
+            },
+        );
+    match farthest {
+        Some((i, dist)) if dist > tolerance => {
+            douglas_peucker(&points[..=i], tolerance, simplified);
+            simplified.pop();
+            douglas_peucker(&points[i..], tolerance, simplified);
+        }
+        _ => {
+            simplified.push(first);
+            simplified.push(last);
+        }
+    }
+}
+
+fn fit_into(points: &[Vec2], error: f32, curves: &mut Vec<QuadCurve>) {
+    if points.len() == 2 {
+        let (first, last) = (points[0], points[1]);
+        curves.push(QuadCurve::new(first, (first + last) / 2., last));
+        return;
+    }
+    let (curve, split, worst) = fit_curve(points);
+    if worst <= error {
+        curves.push(curve);
+    } else {
+        // endpoints have zero error, the split is inside and both halves get 2 points
+        fit_into(&points[..=split], error, curves);
+        fit_into(&points[split..], error, curves);
+    }
+}
+
+/// single curve from the first to the last of at least 3 `points` fitted through the rest,
+/// with the index of the worst fitted point and its distance from the curve
+fn fit_curve(points: &[Vec2]) -> (QuadCurve, usize, f32) {
+    let (first, last) = (points[0], points[points.len() - 1]);
+    let mut curve = QuadCurve::new(first, (first + last) / 2., last);
+    let mut params = vec![0.];
+    let mut length = 0.;
+    for pair in points.windows(2) {
+        length += (pair[1] - pair[0]).length();
+        params.push(length);
+    }
+    params.iter_mut().for_each(|t| *t /= length);
+    for _ in 0..FIT_ITERATIONS {
+        curve.control = fit_control(points, &params, first, last).unwrap_or(curve.control);
+        // ends stay at 0 and 1, they are interpolated exactly
+        let inner = 1..params.len() - 1;
+        for (t, &point) in params[inner.clone()].iter_mut().zip(&points[inner]) {
+            *t = newton_step(&curve, *t, point);
+        }
+    }
+    let (split, worst) = points
+        .iter()
+        .zip(params.iter())
+        .map(|(&point, &t)| (curve.point_at(t) - point).length())
+        .enumerate()
+        .fold(
+            (0, 0.),
+            |worst, (i, dist)| if dist > worst.1 { (i, dist) } else { worst },
+        );
+    (curve, split, worst)
+}
+
+/// control point minimizing squared distances between `points` and the curve from `a`
+/// to `c` evaluated at `params`, `None` if all parameters sit at the ends
+fn fit_control(points: &[Vec2], params: &[f32], a: Vec2, c: Vec2) -> Option<Vec2> {
+    let mut numerator = Vec2::zero();
+    let mut denominator = 0.;
+    for (&point, &t) in points.iter().zip(params.iter()) {
+        let s = 1. - t;
+        let b1 = 2. * s * t;
+        numerator += b1 * (point - s * s * a - t * t * c);
+        denominator += b1 * b1;
+    }
+    if denominator <= f32::EPSILON {
+        return None;
+    }
+    Some(numerator / denominator)
+}
+
+/// moves `t` toward the parameter of the point on `curve` closest to `point`
+fn newton_step(curve: &QuadCurve, t: f32, point: Vec2) -> f32 {
+    let diff = curve.point_at(t) - point;
+    let d1 = curve.tangent_at(t);
+    let d2 = 2. * (curve.a - 2. * curve.control + curve.c);
+    let denominator = d1.dot(d1) + diff.dot(d2);
+    if denominator.abs() <= f32::EPSILON {
+        return t;
+    }
+    clamp(t - diff.dot(d1) / denominator)
+}
+
+/// evaluation shared by bezier curves of any degree, for algorithms written once for all
+pub trait Curve: Sized {
+    fn point_at(&self, t: f32) -> Vec2;
+    /// derivative at parameter `t` (not normalized)
+    fn tangent_at(&self, t: f32) -> Vec2;
+    fn split_at(&self, t: f32) -> (Self, Self);
+    /// largest distance of the control points from the chord. The curve lies in the hull
+    /// of its control points, so it strays from the chord by at most as much
+    fn flatness(&self) -> f32;
+}
+
+/// polyline approximation of `curve`, subdivided until pieces are flatter than `tolerance`.
+/// Consecutive points are never duplicated
+pub fn flatten<C: Curve>(curve: &C, tolerance: f32) -> Vec<Vec2> {
+    let mut points = vec![curve.point_at(0.)];
+    flatten_into(curve, tolerance, FLATTEN_MAX_DEPTH, &mut points);
+    points
+}
+
+fn flatten_into<C: Curve>(curve: &C, tolerance: f32, depth: u32, points: &mut Vec<Vec2>) {
+    if depth == 0 || curve.flatness() < tolerance {
+        let end = curve.point_at(1.);
+        if points.last() != Some(&end) {
+            points.push(end);
+        }
+    } else {
+        let (left, right) = curve.split_at(0.5);
+        flatten_into(&left, tolerance, depth - 1, points);
+        flatten_into(&right, tolerance, depth - 1, points);
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[repr(C)]
+pub struct QuadCurve {
+    pub a: Vec2,
+    pub control: Vec2,
+    pub c: Vec2,
+}
+
+impl From<[Vec2; 3]> for QuadCurve {
+    fn from(points: [Vec2; 3]) -> QuadCurve {
+        QuadCurve::from_array(points)
+    }
+}
+
+impl From<QuadCurve> for [Vec2; 3] {
+    fn from(curve: QuadCurve) -> [Vec2; 3] {
+        curve.as_array()
+    }
+}
+
+#[cfg(feature = "approx")]
+impl approx::AbsDiffEq for QuadCurve {
+    type Epsilon = f32;
+
+    fn default_epsilon() -> f32 {
+        f32::EPSILON
+    }
+
+    fn abs_diff_eq(&self, other: &QuadCurve, epsilon: f32) -> bool {
+        self.approx_eq(other, epsilon)
+    }
+}
+
+impl QuadCurve {
+    pub fn new(a: Vec2, control: Vec2, c: Vec2) -> QuadCurve {
+        QuadCurve { a, control, c }
+    }
+
+    /// the curve has next to no extent: `c` and `control` are within `epsilon` of `a`
+    pub fn is_degenerate(&self, epsilon: f32) -> bool {
+        (self.c - self.a).length() < epsilon && (self.control - self.a).length() < epsilon
+    }
+
+    /// every coordinate of the three points is within `epsilon` of the one of `other`
+    pub fn approx_eq(&self, other: &QuadCurve, epsilon: f32) -> bool {
+        self.a.abs_diff_eq(other.a, epsilon)
+            && self.control.abs_diff_eq(other.control, epsilon)
+            && self.c.abs_diff_eq(other.c, epsilon)
+    }
+
+    /// `a`, `control` and `c` in this order
+    pub fn from_array([a, control, c]: [Vec2; 3]) -> QuadCurve {
+        QuadCurve { a, control, c }
+    }
+
+    /// `a`, `control` and `c` in this order
+    pub fn as_array(&self) -> [Vec2; 3] {
+        [self.a, self.control, self.c]
+    }
+
+    /// position on the curve at parameter `t`, values outside [0, 1] are not clamped
+    /// and extrapolate the parabola past the endpoints
+    pub fn point_at(&self, t: f32) -> Vec2 {
+        let s = 1. - t;
+        s * s * self.a + 2. * s * t * self.control + t * t * self.c
+    }
+
+    /// derivative of the curve at parameter `t` (not normalized)
+    pub fn tangent_at(&self, t: f32) -> Vec2 {
+        2. * (1. - t) * (self.control - self.a) + 2. * t * (self.c - self.control)
+    }
+
+    /// direction the curve leaves `a` in. The tangent vanishes there when the control point
+    /// sits on `a`, the chord gives the direction then
+    pub fn start_direction(&self) -> Vec2 {
+        let tangent = self.tangent_at(0.);
+        if tangent.length_squared() <= f32::EPSILON {
+            self.c - self.a
+        } else {
+            tangent
+        }
+    }
+
+    /// direction the curve arrives at `c` in, see `start_direction`
+    pub fn end_direction(&self) -> Vec2 {
+        let tangent = self.tangent_at(1.);
+        if tangent.length_squared() <= f32::EPSILON {
+            self.c - self.a
+        } else {
+            tangent
+        }
+    }
+
+    /// unit normal at parameter `t`, the tangent rotated by 90 degrees counterclockwise.
+    /// At a cusp (control on the line through `a` and `c` outside of the segment, or
+    /// coincident points) the derivative vanishes and there is no defined direction,
+    /// in that case zero vector is returned
+    pub fn normal_at(&self, t: f32) -> Vec2 {
+        let tangent = self.tangent_at(t);
+        if tangent.length_squared() <= f32::EPSILON {
+            return Vec2::zero();
+        }
+        rot(tangent, 0., 1.).normalize()
+    }
+
+    /// arc length of the whole curve
+    pub fn length(&self) -> f32 {
+        self.length_to(1.)
+    }
+
+    /// arc length from `a` up to the point at parameter `t` (clamped to [0, 1]).
+    /// Integrates the speed with adaptive Gauss-Legendre quadrature, which is exact
+    /// for straight curves and avoids divisions of the closed form
+    pub fn length_to(&self, t: f32) -> f32 {
+        if self.is_degenerate(DEGENERATE_EPSILON) {
+            return 0.;
+        }
+        let t = clamp(t);
+        let whole = self.speed_integral(0., t);
+        self.adaptive_length(0., t, whole, LENGTH_MAX_DEPTH)
+    }
+
+    /// parameter at which arc length from `a` reaches `length`, found by bisection.
+    /// Lengths outside [0, self.length()] give 0 or 1
+    pub fn t_at_length(&self, length: f32) -> f32 {
+        let (mut lo, mut hi) = (0., 1.);
+        for _ in 0..LENGTH_BISECTION_STEPS {
+            let mid = (lo + hi) / 2.;
+            if self.length_to(mid) < length {
+                lo = mid;
+            } else {
+                hi = mid;
+            }
+        }
+        (lo + hi) / 2.
+    }
+
+    fn speed_integral(&self, t0: f32, t1: f32) -> f32 {
+        let half = (t1 - t0) / 2.;
+        let mid = (t0 + t1) / 2.;
+        GAUSS_LEGENDRE
+            .iter()
+            .map(|(x, w)| w * self.tangent_at(mid + half * x).length())
+            .sum::<f32>()
+            * half
+    }
+
+    fn adaptive_length(&self, t0: f32, t1: f32, whole: f32, depth: u32) -> f32 {
+        let mid = (t0 + t1) / 2.;
+        let left = self.speed_integral(t0, mid);
+        let right = self.speed_integral(mid, t1);
+        let sum = left + right;
+        if depth == 0 || (sum - whole).abs() <= LENGTH_TOLERANCE * sum {
+            sum
+        } else {
+            self.adaptive_length(t0, mid, left, depth - 1)
+                + self.adaptive_length(mid, t1, right, depth - 1)
+        }
+    }
+
+    pub fn vertices(&self, width: f32) -> (Vec<Vertex>, Vec<u32>) {
+        self.vertices_tapered(width, width)
+    }
+
+    /// quad covering the curve with width changing from `start_width` at `a`
+    /// to `end_width` at `c`, the box is padded by the larger of the two
+    /// and the margin of the default antialiased edge
+    pub fn vertices_tapered(&self, start_width: f32, end_width: f32) -> (Vec<Vertex>, Vec<u32>) {
+        let pad = start_width.max(end_width) + aa_margin(DEFAULT_AA_WIDTH);
+        self.padded_vertices(start_width, end_width, pad)
+    }
+
+    fn padded_vertices(
+        &self,
+        start_width: f32,
+        end_width: f32,
+        pad: f32,
+    ) -> (Vec<Vertex>, Vec<u32>) {
+        let [a, b, c, d] = self.oriented_bounding_box(pad);
+        let indices = vec![0, 1, 2, 0, 2, 3];
+        let vertex = |position| Vertex {
+            position,
+            curve: *self,
+            thickness: start_width,
+            end_thickness: end_width,
+            color: DEFAULT_COLOR,
+            end_color: DEFAULT_COLOR,
+            caps: Vec2::zero(),
+            dash_offset: 0.,
+            dash: Vec4::zero(),
+        };
+        (vec![vertex(a), vertex(b), vertex(c), vertex(d)], indices)
+    }
+
+    /// parameter and position of the point on the curve closest to `pos`.
+    /// Same cubic solution as `sdBezier` in the fragment shader
+    /// (https://www.shadertoy.com/view/MlKcDD) so cpu and gpu agree on distances
+    pub fn closest_point(&self, pos: Vec2) -> (f32, Vec2) {
+        let a = self.control - self.a;
+        let b = self.a - 2. * self.control + self.c;
+        let c = a * 2.;
+        let d = self.a - pos;
+        let point = |t: f32| self.a + (c + b * t) * t;
+        let bb = b.dot(b);
+        if bb <= f32::EPSILON {
+            // no curvature, the curve is the segment a + c * t
+            let cc = c.dot(c);
+            let t = if cc <= f32::EPSILON {
+                0.
+            } else {
+                clamp(-d.dot(c) / cc)
+            };
+            return (t, point(t));
+        }
+        let kk = 1. / bb;
+        let kx = kk * a.dot(b);
+        let ky = kk * (2. * a.dot(a) + d.dot(b)) / 3.;
+        let kz = kk * d.dot(a);
+        let p = ky - kx * kx;
+        let p3 = p * p * p;
+        let q = kx * (2. * kx * kx - 3. * ky) + kz;
+        let h = q * q + 4. * p3;
+        if h >= 0. {
+            let h = h.sqrt();
+            let x = vec2((h - q) / 2., (-h - q) / 2.);
+            let t = clamp(x.x.cbrt() + x.y.cbrt() - kx);
+            (t, point(t))
+        } else {
+            let z = (-p).sqrt();
+            // rounding can push the argument slightly out of acos domain
+            let v = (q / (p * z * 2.)).clamp(-1., 1.).acos() / 3.;
+            let m = v.cos();
+            let n = v.sin() * 3_f32.sqrt();
+            let t0 = clamp((m + m) * z - kx);
+            let t1 = clamp((-n - m) * z - kx);
+            // the third root cannot be the closest
+            let (p0, p1) = (point(t0), point(t1));
+            if (p0 - pos).length_squared() <= (p1 - pos).length_squared() {
+                (t0, p0)
+            } else {
+                (t1, p1)
+            }
+        }
+    }
+
+    /// unsigned distance from `pos` to the curve, matches `sdBezier` of the fragment shader
+    pub fn distance(&self, pos: Vec2) -> f32 {
+        (self.closest_point(pos).1 - pos).length()
+    }
+
+    /// crossings with the segment `p0`-`p1` as parameter on the curve and point, sorted by
+    /// parameter. Signed distance to the line is a quadratic in `t` so roots are exact,
+    /// a tangent touch gives a single hit
+    pub fn intersect_line(&self, p0: Vec2, p1: Vec2) -> Vec<(f32, Vec2)> {
+        let dir = p1 - p0;
+        let len2 = dir.length_squared();
+        if len2 <= f32::EPSILON {
+            return vec![];
+        }
+        let n = dir.perp();
+        let mut hits: Vec<(f32, Vec2)> = quadratic_roots(
+            n.dot(self.a - 2. * self.control + self.c),
+            2. * n.dot(self.control - self.a),
+            n.dot(self.a - p0),
+        )
+        .into_iter()
+        .filter(|t| (0. ..=1.).contains(t))
+        .map(|t| (t, self.point_at(t)))
+        .filter(|(_, point)| (0. ..=1.).contains(&((*point - p0).dot(dir) / len2)))
+        .collect();
+        hits.sort_by(|(t0, _), (t1, _)| t0.partial_cmp(t1).unwrap());
+        hits
+    }
+
+    /// intersection points with `other`, found by splitting both curves while their
+    /// bounding boxes overlap until the pieces are smaller than `tolerance`.
+    /// Points closer than `tolerance` to each other are reported once. Where the curves run
+    /// along each other only the ends of the shared stretch are reported
+    pub fn intersect(&self, other: &QuadCurve, tolerance: f32) -> Vec<Vec2> {
+        let (mut crossings, overlap_ends) = self.crossings_and_overlap_ends(other, tolerance);
+        crossings.extend(overlap_ends);
+        crossings
+    }
+
+    /// `intersect` with the ends of shared stretches apart from the crossings
+    fn crossings_and_overlap_ends(
+        &self,
+        other: &QuadCurve,
+        tolerance: f32,
+    ) -> (Vec<Vec2>, Vec<Vec2>) {
+        let mut crossings = vec![];
+        let mut overlaps = vec![];
+        self.intersect_into(
+            other,
+            tolerance,
+            INTERSECT_MAX_DEPTH,
+            &mut crossings,
+            &mut overlaps,
+        );
+        // consecutive overlapping pieces share an end, the stretch ends are shared by none
+        let near = |p: Vec2, q: Vec2| (p - q).length() < tolerance;
+        let mut ends: Vec<Vec2> = vec![];
+        for (i, (from, to)) in overlaps.iter().enumerate() {
+            for end in [*from, *to].iter() {
+                let shared = overlaps
+                    .iter()
+                    .enumerate()
+                    .any(|(j, (p, q))| j != i && (near(*end, *p) || near(*end, *q)));
+                if !shared && crossings.iter().chain(&ends).all(|p| !near(*p, *end)) {
+                    ends.push(*end);
+                }
+            }
+        }
+        (crossings, ends)
+    }
+
+    /// collects crossings away from the stretches in `overlaps`, the search stops once
+    /// `INTERSECT_MAX_CROSSINGS` are found
+    fn intersect_into(
+        &self,
+        other: &QuadCurve,
+        tolerance: f32,
+        depth: u32,
+        crossings: &mut Vec<Vec2>,
+        overlaps: &mut Vec<(Vec2, Vec2)>,
+    ) {
+        let (bb0, bb1) = (self.bounding_box(), other.bounding_box());
+        if !boxes_overlap(bb0, bb1) || crossings.len() >= INTERSECT_MAX_CROSSINGS {
+            return;
+        }
+        let near = |p: Vec2, q: Vec2| (p - q).length() < tolerance;
+        let on_overlap = |point: Vec2, overlaps: &[(Vec2, Vec2)]| {
+            overlaps
+                .iter()
+                .any(|(from, to)| distance_to_segment(point, *from, *to) < tolerance)
+        };
+        let small = |(mi, ma): (Vec2, Vec2)| (ma - mi).max_element() < tolerance;
+        let point = if depth == 0 || (small(bb0) && small(bb1)) {
+            (self.point_at(0.5) + other.point_at(0.5)) / 2.
+        } else if let Some((from, to)) = self.collinear_overlap(other, tolerance) {
+            if !near(from, to) {
+                // pieces running along each other touch everywhere, only the stretch counts
+                crossings.retain(|p| distance_to_segment(*p, from, to) >= tolerance);
+                overlaps.push((from, to));
+                return;
+            }
+            (from + to) / 2.
+        } else {
+            let (l0, r0) = self.split();
+            let (l1, r1) = other.split();
+            for (c0, c1) in [(l0, l1), (l0, r1), (r0, l1), (r0, r1)].iter() {
+                c0.intersect_into(c1, tolerance, depth - 1, crossings, overlaps);
+            }
+            return;
+        };
+        if !on_overlap(point, overlaps) && crossings.iter().all(|p| !near(*p, point)) {
+            crossings.push(point);
+        }
+    }
+
+    /// stretch shared by two curves that are straight and lie on one line within
+    /// `tolerance`. Splitting such pieces would only find more points of the stretch
+    fn collinear_overlap(&self, other: &QuadCurve, tolerance: f32) -> Option<(Vec2, Vec2)> {
+        let chord = self.c - self.a;
+        let len = chord.length();
+        if len < tolerance || (other.c - other.a).length() < tolerance {
+            return None;
+        }
+        let dir = chord / len;
+        let off_line = |p: Vec2| wedge(dir, p - self.a).abs() >= tolerance;
+        let bent =
+            |curve: &QuadCurve| distance_to_segment(curve.control, curve.a, curve.c) >= tolerance;
+        if bent(self) || bent(other) || off_line(other.a) || off_line(other.c) {
+            return None;
+        }
+        let (o0, o1) = (dir.dot(other.a - self.a), dir.dot(other.c - self.a));
+        let from = o0.min(o1).max(0.);
+        let to = o0.max(o1).min(len);
+        // boxes overlap so the pieces are at most touching when `to < from`
+        Some((self.a + dir * from, self.a + dir * to.max(from)))
+    }
+
+    /// corners of a box around the curve padded by `width`, aligned with the chord from `a`
+    /// to `c` so it's tighter than the axis aligned one. This is the quad a curve is drawn on.
+    /// Basically https://www.iquilezles.org/www/articles/bezierbbox/bezierbbox.htm
+    /// with extra rotation.
+    /// In the frame where the chord points along +x the corners are min, (min x, max y),
+    /// max and (max x, min y): consecutive corners share an edge, so they can be drawn as a
+    /// line loop, and go clockwise with y axis up (counterclockwise on screen)
+    pub fn oriented_bounding_box(&self, width: f32) -> [Vec2; 4] {
+        let dir = self.c - self.a;
+        // no direction to align with when endpoints coincide, normalizing would give NaNs
+        if dir.length_squared() <= f32::EPSILON {
+            let (mi, ma) = self.bounding_box();
+            let (mi, ma) = bounding_box_frame(mi, ma, width);
+            return [mi, vec2(mi.x, ma.y), ma, vec2(ma.x, mi.y)];
+        }
+        let ndir = dir.normalize();
+        let ox = vec2(1., 0.);
+        let sinb = wedge(ndir, ox);
+        let cosb = (self.c - self.a).normalize().dot(ox);
+        // align with Ox axis
+        let p0 = self.a;
+        let p0p1 = self.control - self.a;
+        let p1 = p0 + rot(p0p1, cosb, sinb);
+        let p2 = p0 + vec2(dir.length(), 0.);
+
+        // extrema in this coordinates, `extrema` skips axes where the derivative is
+        // constant instead of dividing by zero
+        let (mi, ma) = QuadCurve::new(p0, p1, p2).bounding_box();
+        let (mi, ma) = bounding_box_frame(mi, ma, width);
+        // now align back with bezier
+        let ma_rotated = p0 + rot(ma - p0, cosb, -sinb);
+        let mi_rotated = p0 + rot(mi - p0, cosb, -sinb);
+        let offset = ndir.dot(mi_rotated - ma_rotated) * ndir;
+        let b = ma_rotated + offset;
+        let d = mi_rotated - offset;
+        [mi_rotated, b, ma_rotated, d]
+    }
+
+    /// signed curvature at parameter `t`, positive when the curve turns counterclockwise
+    /// (toward `normal_at`). At a cusp the curvature is infinite
+    pub fn curvature_at(&self, t: f32) -> f32 {
+        let d1 = self.tangent_at(t);
+        let d2 = 2. * (self.a - 2. * self.control + self.c);
+        let speed = d1.length();
+        if speed <= f32::EPSILON {
+            return f32::INFINITY;
+        }
+        wedge(d1, d2) / (speed * speed * speed)
+    }
+
+    /// contribution of the curve to the area of a closed path by green's theorem, the
+    /// integral of `wedge(p, p') / 2` along it. For a quadratic that's
+    /// `(2 a^control + 2 control^c + a^c) / 6` with `^` the wedge product
+    pub fn signed_area_term(&self) -> f32 {
+        (2. * wedge(self.a, self.control)
+            + 2. * wedge(self.control, self.c)
+            + wedge(self.a, self.c))
+            / 6.
+    }
+
+    /// contribution of the curve to the first moments of the area of a closed path by
+    /// green's theorem, the integrals of `x^2 y' / 2` and `-y^2 x' / 2` along it.
+    /// Both are polynomials of degree 5 in `t`, which 5 point gauss-legendre integrates exactly
+    pub fn first_moment_term(&self) -> Vec2 {
+        GAUSS_LEGENDRE
+            .iter()
+            .map(|&(x, w)| {
+                let t = (x + 1.) / 2.;
+                let (p, d) = (self.point_at(t), self.tangent_at(t));
+                w / 2. * vec2(p.x * p.x * d.y, -p.y * p.y * d.x) / 2.
+            })
+            .fold(Vec2::zero(), |sum, term| sum + term)
+    }
+
+    /// parameters inside (0, 1) where the x or the y derivative vanishes, sorted.
+    /// An axis with control at the middle of the endpoints has constant derivative and no extremum
+    pub fn extrema(&self) -> Vec<f32> {
+        let num = self.a - self.control;
+        let den = self.a - 2. * self.control + self.c;
+        let mut extrema: Vec<f32> = [(num.x, den.x), (num.y, den.y)]
+            .iter()
+            .filter(|(_, den)| *den != 0.)
+            .map(|(num, den)| num / den)
+            .filter(|t| *t > 0. && *t < 1.)
+            .collect();
+        extrema.sort_by(|t0, t1| t0.partial_cmp(t1).unwrap());
+        extrema.dedup();
+        extrema
+    }
+
+    /// bounding box with edges parallel to Ox Oy
+    pub fn bounding_box(&self) -> (Vec2, Vec2) {
+        let mut mi = self.a.min(self.c);
+        let mut ma = self.a.max(self.c);
+        for t in self.extrema() {
+            let q = self.point_at(t);
+            mi = mi.min(q);
+            ma = ma.max(q);
+        }
+        (mi, ma)
+    }
+
+    pub fn scale(&self, scale: f32) -> QuadCurve {
+        QuadCurve {
+            a: self.a * scale,
+            control: self.control * scale,
+            c: self.c * scale,
+        }
+    }
+
+    /// scales around `origin` instead of the world origin
+    pub fn scale_about(&self, factor: f32, origin: Vec2) -> QuadCurve {
+        self.translate(-origin).scale(factor).translate(origin)
+    }
+
+    pub fn translate(&self, offset: Vec2) -> QuadCurve {
+        QuadCurve {
+            a: self.a + offset,
+            control: self.control + offset,
+            c: self.c + offset,
+        }
+    }
+
+    /// rotation by `angle` radians around `origin`, counterclockwise when y axis points up
+    pub fn rotate(&self, angle: f32, origin: Vec2) -> QuadCurve {
+        let (sinb, cosb) = angle.sin_cos();
+        let rotate = |p: Vec2| origin + rot(p - origin, cosb, sinb);
+        QuadCurve {
+            a: rotate(self.a),
+            control: rotate(self.control),
+            c: rotate(self.c),
+        }
+    }
+
+    /// applies 2d affine transform `m` to all points, exact since beziers are affine invariant
+    pub fn transform(&self, m: Mat3) -> QuadCurve {
+        QuadCurve {
+            a: m.transform_point2(self.a),
+            control: m.transform_point2(self.control),
+            c: m.transform_point2(self.c),
+        }
+    }
+
+    /// same curve traced from `c` to `a`
+    pub fn reverse(&self) -> QuadCurve {
+        QuadCurve::new(self.c, self.control, self.a)
+    }
+
+    /// degree elevation, returns the points of the cubic bezier tracing the same curve
+    pub fn to_cubic(&self) -> (Vec2, Vec2, Vec2, Vec2) {
+        (
+            self.a,
+            self.a + 2. / 3. * (self.control - self.a),
+            self.c + 2. / 3. * (self.control - self.c),
+            self.c,
+        )
+    }
+
+    pub fn split(&self) -> (QuadCurve, QuadCurve) {
+        self.split_at(0.5)
+    }
+
+    /// `n` consecutive pieces covering equal parameter intervals, empty for `n == 0`.
+    /// The remainder is split repeatedly so neighbours share their endpoints exactly
+    pub fn subdivide(&self, n: usize) -> Vec<QuadCurve> {
+        let mut pieces = Vec::with_capacity(n);
+        let mut rest = *self;
+        for i in 0..n.saturating_sub(1) {
+            let (piece, remainder) = rest.split_at(1. / (n - i) as f32);
+            pieces.push(piece);
+            rest = remainder;
+        }
+        if n > 0 {
+            pieces.push(rest);
+        }
+        pieces
+    }
+
+    /// `n` consecutive pieces of equal arc length, empty for `n == 0`. Split parameters come
+    /// from `t_at_length`, so piece lengths agree to the quadrature tolerance
+    /// (relative 1e-5) plus f32 rounding of the splits, well under a percent
+    pub fn subdivide_by_length(&self, n: usize) -> Vec<QuadCurve> {
+        let mut pieces = Vec::with_capacity(n);
+        let step = self.length() / n as f32;
+        let mut rest = *self;
+        let mut t_prev = 0.;
+        for i in 1..n {
+            let t = self.t_at_length(step * i as f32);
+            // rounding can put the last splits at 1, the pieces are empty then
+            let u = if t_prev < 1. {
+                (t - t_prev) / (1. - t_prev)
+            } else {
+                0.
+            };
+            let (piece, remainder) = rest.split_at(u);
+            pieces.push(piece);
+            rest = remainder;
+            t_prev = t;
+        }
+        if n > 0 {
+            pieces.push(rest);
+        }
+        pieces
+    }
+
+    /// polyline approximation from `a` to `c`. Subdivides until the control point is
+    /// closer than `tolerance` to the chord, which bounds deviation of the polyline
+    /// from the curve by `tolerance / 2`. Consecutive points are never duplicated
+    pub fn flatten(&self, tolerance: f32) -> Vec<Vec2> {
+        flatten(self, tolerance)
+    }
+
+    /// polyline of the curve shifted by `distance` along `normal_at`, negative distances
+    /// go to the other side. Subdivides until the offset between samples is closer than
+    /// `tolerance` to a straight segment. Inner offsets tighter than the curvature radius
+    /// fold over themselves, the loop is kept as is. At a cusp the normal is zero and the
+    /// point stays on the curve
+    pub fn offset(&self, distance: f32, tolerance: f32) -> Vec<Vec2> {
+        let mut points = vec![self.offset_at(0., distance)];
+        self.offset_into(0., 1., distance, tolerance, FLATTEN_MAX_DEPTH, &mut points);
+        points
+    }
+
+    fn offset_at(&self, t: f32, distance: f32) -> Vec2 {
+        self.point_at(t) + self.normal_at(t) * distance
+    }
+
+    fn offset_into(
+        &self,
+        t0: f32,
+        t1: f32,
+        distance: f32,
+        tolerance: f32,
+        depth: u32,
+        points: &mut Vec<Vec2>,
+    ) {
+        let tm = (t0 + t1) / 2.;
+        let (p0, pm, p1) = (
+            self.offset_at(t0, distance),
+            self.offset_at(tm, distance),
+            self.offset_at(t1, distance),
+        );
+        let flat = distance_to_segment(pm, p0, p1) < tolerance
+            && self.normal_at(t0).dot(self.normal_at(t1)) >= OFFSET_MIN_NORMAL_DOT;
+        if depth == 0 || flat {
+            if points.last() != Some(&p1) {
+                points.push(p1);
+            }
+        } else {
+            self.offset_into(t0, tm, distance, tolerance, depth - 1, points);
+            self.offset_into(tm, t1, distance, tolerance, depth - 1, points);
+        }
+    }
+
+    /// de Casteljau subdivision at parameter `t`, both halves share the split point exactly
+    pub fn split_at(&self, t: f32) -> (QuadCurve, QuadCurve) {
+        let q0 = self.a.lerp(self.control, t);
+        let q1 = self.control.lerp(self.c, t);
+        let r0 = q0.lerp(q1, t);
+        (
+            QuadCurve::new(self.a, q0, r0),
+            QuadCurve::new(r0, q1, self.c),
+        )
+    }
+}
+
+impl Curve for QuadCurve {
+    fn point_at(&self, t: f32) -> Vec2 {
+        QuadCurve::point_at(self, t)
+    }
+
+    fn tangent_at(&self, t: f32) -> Vec2 {
+        QuadCurve::tangent_at(self, t)
+    }
+
+    fn split_at(&self, t: f32) -> (QuadCurve, QuadCurve) {
+        QuadCurve::split_at(self, t)
+    }
+
+    fn flatness(&self) -> f32 {
+        distance_to_segment(self.control, self.a, self.c)
+    }
+}
+
+/// cubic bezier from `a` to `b` with control points `c1` and `c2`. It is drawn
+/// as a sequence of quadratic curves, see `to_quads`
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CubicCurve {
+    pub a: Vec2,
+    pub c1: Vec2,
+    pub c2: Vec2,
+    pub b: Vec2,
+}
+
+impl CubicCurve {
+    pub fn new(a: Vec2, c1: Vec2, c2: Vec2, b: Vec2) -> CubicCurve {
+        CubicCurve { a, c1, c2, b }
+    }
+
+    pub fn point_at(&self, t: f32) -> Vec2 {
+        let s = 1. - t;
+        s * s * s * self.a
+            + 3. * s * s * t * self.c1
+            + 3. * s * t * t * self.c2
+            + t * t * t * self.b
+    }
+
+    /// de Casteljau subdivision at parameter `t`
+    pub fn split_at(&self, t: f32) -> (CubicCurve, CubicCurve) {
+        let p0 = self.a.lerp(self.c1, t);
+        let p1 = self.c1.lerp(self.c2, t);
+        let p2 = self.c2.lerp(self.b, t);
+        let q0 = p0.lerp(p1, t);
+        let q1 = p1.lerp(p2, t);
+        let r = q0.lerp(q1, t);
+        (
+            CubicCurve::new(self.a, p0, q0, r),
+            CubicCurve::new(r, q1, p2, self.b),
+        )
+    }
+
+    /// quadratic curves deviating from the cubic by at most `tolerance`. Each piece is
+    /// the midpoint approximation, its distance to the cubic is bounded by
+    /// `sqrt(3) / 36 * |b - 3 c2 + 3 c1 - a|`, pieces above the tolerance are halved
+    pub fn to_quads(&self, tolerance: f32) -> Vec<QuadCurve> {
+        let mut quads = vec![];
+        self.quads_into(tolerance, TO_QUADS_MAX_DEPTH, &mut quads);
+        quads
+    }
+
+    fn quads_into(&self, tolerance: f32, depth: u32, quads: &mut Vec<QuadCurve>) {
+        let error = 3f32.sqrt() / 36. * (self.b - 3. * self.c2 + 3. * self.c1 - self.a).length();
+        if depth == 0 || error <= tolerance {
+            let control = (3. * (self.c1 + self.c2) - self.a - self.b) / 4.;
+            quads.push(QuadCurve::new(self.a, control, self.b));
+        } else {
+            let (left, right) = self.split_at(0.5);
+            left.quads_into(tolerance, depth - 1, quads);
+            right.quads_into(tolerance, depth - 1, quads);
+        }
+    }
+}
+
+impl Curve for CubicCurve {
+    fn point_at(&self, t: f32) -> Vec2 {
+        CubicCurve::point_at(self, t)
+    }
+
+    fn tangent_at(&self, t: f32) -> Vec2 {
+        let s = 1. - t;
+        3. * s * s * (self.c1 - self.a)
+            + 6. * s * t * (self.c2 - self.c1)
+            + 3. * t * t * (self.b - self.c2)
+    }
+
+    fn split_at(&self, t: f32) -> (CubicCurve, CubicCurve) {
+        CubicCurve::split_at(self, t)
+    }
+
+    fn flatness(&self) -> f32 {
+        distance_to_segment(self.c1, self.a, self.b)
+            .max(distance_to_segment(self.c2, self.a, self.b))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn arch() -> QuadCurve {
+        QuadCurve::new(vec2(0., 0.), vec2(50., 100.), vec2(100., 0.))
+    }
+
+    #[test]
+    fn point_at_ends_and_middle() {
+        let curve = arch();
+        assert_eq!(curve.point_at(0.), curve.a);
+        assert_eq!(curve.point_at(1.), curve.c);
+        // de Casteljau midpoint, where the halves of `split` join
+        let (left, right) = curve.split();
+        assert_eq!(curve.point_at(0.5), vec2(50., 50.));
+        assert_eq!(curve.point_at(0.5), left.c);
+        assert_eq!(curve.point_at(0.5), right.a);
+    }
+
+    #[test]
+    fn tangent_matches_finite_difference() {
+        let curve = arch();
+        let h = 1e-3;
+        for &t in &[0.1, 0.3, 0.5, 0.7, 0.9] {
+            let diff = (curve.point_at(t + h) - curve.point_at(t - h)) / (2. * h);
+            let tangent = curve.tangent_at(t);
+            assert!(
+                (diff - tangent).length() < 1e-2 * tangent.length(),
+                "t {}: {:?} vs {:?}",
+                t,
+                diff,
+                tangent
+            );
+            let normal = curve.normal_at(t);
+            assert!((normal.length() - 1.).abs() < 1e-5);
+            assert!(normal.dot(tangent).abs() < 1e-3);
+            assert!(wedge(tangent, normal) > 0.);
+        }
+    }
+
+    #[test]
+    fn length_of_straight_and_curved() {
+        // control on the chord, closed form divisions would give NaNs here
+        let line = QuadCurve::new(vec2(0., 0.), vec2(1., 2.), vec2(3., 6.));
+        assert!((line.length() - (line.c - line.a).length()).abs() < 1e-4);
+        assert!((line.length_to(0.5) - line.point_at(0.5).length()).abs() < 1e-4);
+
+        let curve = arch();
+        let n = 10000;
+        let polyline: f32 = (0..n)
+            .map(|i| {
+                let (t0, t1) = (i as f32 / n as f32, (i + 1) as f32 / n as f32);
+                (curve.point_at(t1) - curve.point_at(t0)).length()
+            })
+            .sum();
+        assert!((curve.length() - polyline).abs() < 1e-2);
+        assert_eq!(curve.length_to(0.), 0.);
+        // symmetric curve, half the parameter range is half the length
+        assert!((curve.length_to(0.5) - curve.length() / 2.).abs() < 1e-3);
+    }
+
+    #[test]
+    fn split_at_halves_trace_the_curve() {
+        let curve = arch();
+        for &t in &[0.25, 0.5, 0.8] {
+            let (left, right) = curve.split_at(t);
+            assert_eq!(left.c, right.a);
+            for &u in &[0., 0.3, 0.6, 1.] {
+                let on_left = left.point_at(u) - curve.point_at(t * u);
+                let on_right = right.point_at(u) - curve.point_at(t + (1. - t) * u);
+                assert!(on_left.length() < 1e-3, "t {} u {}", t, u);
+                assert!(on_right.length() < 1e-3, "t {} u {}", t, u);
+            }
+        }
+    }
+
+    #[test]
+    fn flatten_stays_within_tolerance() {
+        let curve = arch();
+        let tolerance = 0.1;
+        let points = curve.flatten(tolerance);
+        assert_eq!(points.first(), Some(&curve.a));
+        assert_eq!(points.last(), Some(&curve.c));
+        assert!(points.windows(2).all(|w| w[0] != w[1]));
+        for i in 0..=1000 {
+            let sample = curve.point_at(i as f32 / 1000.);
+            let deviation = points
+                .windows(2)
+                .map(|w| distance_to_segment(sample, w[0], w[1]))
+                .fold(f32::INFINITY, f32::min);
+            assert!(deviation < tolerance, "{} at {:?}", deviation, sample);
+        }
+
+        let straight = QuadCurve::new(vec2(0., 0.), vec2(5., 0.01), vec2(10., 0.));
+        assert_eq!(straight.flatten(tolerance), vec![straight.a, straight.c]);
+    }
+
+    fn path_of(curves: Vec<QuadCurve>) -> BezierPath {
+        BezierPath {
+            curves,
+            ..Default::default()
+        }
+    }
+
+    fn stroked(points: &[Vec2]) -> BezierPath {
+        let mut path = BezierPath::default();
+        for &point in points {
+            path.stroke(point);
+        }
+        path
+    }
+
+    #[test]
+    fn undo_twice_redo_once() {
+        let points: Vec<Vec2> = (0..7).map(|i| vec2(i as f32, (i % 2) as f32)).collect();
+        let mut path = stroked(&points);
+        let curves = path.curves.clone();
+        assert_eq!(curves.len(), 3);
+
+        path.undo();
+        path.undo();
+        assert_eq!(path.curves, &curves[..1]);
+        path.redo();
+        assert_eq!(path.curves, &curves[..2]);
+        assert_eq!(path.last, Some(curves[1].c));
+        assert_eq!(path.control, None);
+
+        // a new point drops the rest of the history
+        path.stroke(vec2(10., 10.));
+        path.redo();
+        assert_eq!(path.curves, &curves[..2]);
+    }
+
+    #[test]
+    fn indices_past_u16() {
+        let path = path_of(
+            (0..20000)
+                .map(|i| {
+                    let x = i as f32;
+                    QuadCurve::new(vec2(x, 0.), vec2(x + 0.5, 1.), vec2(x + 1., 0.))
+                })
+                .collect(),
+        );
+        let (vertices, indices) = path.vertices(2.);
+        assert_eq!(vertices.len(), 80000);
+        assert_eq!(indices.iter().max(), Some(&79999));
+    }
+
+    fn finite(p: Vec2) -> bool {
+        p.x.is_finite() && p.y.is_finite()
+    }
+
+    #[test]
+    fn vertices_of_closed_curve_are_finite() {
+        let curve = QuadCurve::new(vec2(5., 5.), vec2(20., 10.), vec2(5., 5.));
+        let (vertices, _) = curve.vertices(10.);
+        assert_eq!(vertices.len(), 4);
+        for vertex in vertices {
+            assert!(finite(vertex.position), "{:?}", vertex.position);
+        }
+        let point = QuadCurve::new(vec2(5., 5.), vec2(5., 5.), vec2(5., 5.));
+        assert!(point.vertices(10.).0.iter().all(|v| finite(v.position)));
+    }
+
+    #[test]
+    fn closest_point_on_the_curve() {
+        let curve = arch();
+        for &t in &[0., 0.2, 0.5, 0.9, 1.] {
+            let point = curve.point_at(t);
+            let (closest_t, closest) = curve.closest_point(point);
+            assert!((closest - point).length() < 1e-3, "t {}", t);
+            assert!((closest_t - t).abs() < 1e-3, "t {}", t);
+        }
+    }
+
+    #[test]
+    fn distance_matches_sampling() {
+        let curve = arch();
+        let samples: Vec<Vec2> = (0..=2000)
+            .map(|i| curve.point_at(i as f32 / 2000.))
+            .collect();
+        for i in 0..=10 {
+            for j in 0..=10 {
+                let p = vec2(-20. + 14. * i as f32, -30. + 15. * j as f32);
+                let sampled = samples
+                    .iter()
+                    .map(|s| (*s - p).length())
+                    .fold(f32::INFINITY, f32::min);
+                assert!((curve.distance(p) - sampled).abs() < 0.1, "{:?}", p);
+            }
+        }
+    }
+
+    #[test]
+    fn transform_commutes_with_sampling() {
+        let curve = arch();
+        let m = Mat3::from_scale_angle_translation(vec2(2., 0.5), 0.7, vec2(-3., 8.));
+        let transformed = curve.transform(m);
+        for &t in &[0., 0.25, 0.5, 0.75, 1.] {
+            let expected = m.transform_point2(curve.point_at(t));
+            assert!(
+                (transformed.point_at(t) - expected).length() < 1e-3,
+                "t {}",
+                t
+            );
+        }
+    }
+
+    #[test]
+    fn rotate_by_right_angle() {
+        let curve = QuadCurve::new(vec2(1., 0.), vec2(2., 1.), vec2(3., 0.));
+        let rotated = curve.rotate(std::f32::consts::FRAC_PI_2, Vec2::zero());
+        let expected = QuadCurve::new(vec2(0., 1.), vec2(-1., 2.), vec2(0., 3.));
+        assert!(rotated.approx_eq(&expected, 1e-5), "{:?}", rotated);
+        // around the start point it stays in place
+        let rotated = curve.rotate(std::f32::consts::FRAC_PI_2, curve.a);
+        let expected = QuadCurve::new(vec2(1., 0.), vec2(0., 1.), vec2(1., 2.));
+        assert!(rotated.approx_eq(&expected, 1e-5), "{:?}", rotated);
+        assert_eq!(curve.translate(vec2(1., 2.)).a, vec2(2., 2.));
+    }
+
+    #[test]
+    fn bounding_box_contains_samples() {
+        let path = path_of(vec![
+            arch(),
+            QuadCurve::new(vec2(100., 0.), vec2(150., -80.), vec2(120., 40.)),
+        ]);
+        let (mi, ma) = path.bounding_box().unwrap();
+        for (_, _, p) in path.iter_samples(200) {
+            assert!(
+                p.x >= mi.x - 1e-4 && p.y >= mi.y - 1e-4,
+                "{:?} below {:?}",
+                p,
+                mi
+            );
+            assert!(
+                p.x <= ma.x + 1e-4 && p.y <= ma.y + 1e-4,
+                "{:?} above {:?}",
+                p,
+                ma
+            );
+        }
+        // the arch top and the dip of the second curve are reached
+        assert!((ma.y - 50.).abs() < 1e-3);
+        assert!(mi.y < -20.);
+        assert!(BezierPath::default().bounding_box().is_none());
+    }
+
+    #[test]
+    fn cubic_elevation_traces_the_same_curve() {
+        let curve = arch();
+        let (a, c1, c2, b) = curve.to_cubic();
+        let cubic = CubicCurve::new(a, c1, c2, b);
+        for i in 0..=8 {
+            let t = i as f32 / 8.;
+            assert!(
+                (cubic.point_at(t) - curve.point_at(t)).length() < 1e-3,
+                "t {}",
+                t
+            );
+        }
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_round_trip() {
+        let mut path = stroked(&[
+            vec2(0., 0.),
+            vec2(5., 10.),
+            vec2(10., 0.),
+            vec2(15., -10.),
+            vec2(20., 0.),
+            vec2(25., 10.),
+            vec2(30., 0.),
+            vec2(35., 5.),
+        ]);
+        path.cap = CapStyle::Square;
+        path.dash_pattern = Some(vec![4., 2.]);
+        path.gradient = Some((Vec4::new(1., 0., 0., 1.), Vec4::new(0., 0., 1., 1.)));
+        assert_eq!(path.curves.len(), 3);
+        let json = serde_json::to_string(&path).unwrap();
+        let parsed: BezierPath = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed, path);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn deserialize_path_without_style_fields() {
+        let path = stroked(&[vec2(0., 0.), vec2(5., 10.), vec2(10., 0.), vec2(15., -10.)]);
+        let mut json = serde_json::to_value(&path).unwrap();
+        let fields = json.as_object_mut().unwrap();
+        for key in &[
+            "gradient",
+            "cap",
+            "join",
+            "dash_pattern",
+            "min_point_distance",
+        ] {
+            assert!(fields.remove(*key).is_some(), "{} isn't serialized", key);
+        }
+        let parsed: BezierPath = serde_json::from_value(json).unwrap();
+        assert_eq!(parsed, path);
+    }
+
+    #[test]
+    fn line_crosses_arch_twice() {
+        let hits = arch().intersect_line(vec2(-10., 25.), vec2(110., 25.));
+        assert_eq!(hits.len(), 2);
+        for (t, point) in hits.iter() {
+            assert!((point.y - 25.).abs() < 1e-3);
+            assert!((arch().point_at(*t) - *point).length() < 1e-4);
+        }
+        assert!(hits[0].0 < hits[1].0);
+        // above the top there is nothing to hit
+        assert!(arch()
+            .intersect_line(vec2(-10., 60.), vec2(110., 60.))
+            .is_empty());
+    }
+
+    #[test]
+    fn line_touches_arch_top_once() {
+        let hits = arch().intersect_line(vec2(-10., 50.), vec2(110., 50.));
+        assert_eq!(hits, vec![(0.5, vec2(50., 50.))]);
+    }
+
+    #[test]
+    fn segment_stops_short_of_arch() {
+        // the line y = 25 crosses the arch near x = 14.6 and x = 85.4
+        assert!(arch()
+            .intersect_line(vec2(-10., 25.), vec2(10., 25.))
+            .is_empty());
+        let hits = arch().intersect_line(vec2(-10., 25.), vec2(50., 25.));
+        assert_eq!(hits.len(), 1, "{:?}", hits);
+        assert!(hits[0].0 < 0.5);
+    }
+
+    #[test]
+    fn curves_forming_an_x() {
+        let first = QuadCurve::new(vec2(0., 0.), vec2(45., 55.), vec2(100., 100.));
+        let second = QuadCurve::new(vec2(0., 100.), vec2(55., 55.), vec2(100., 0.));
+        let points = first.intersect(&second, 0.01);
+        assert_eq!(points.len(), 1, "{:?}", points);
+        // the curves mirror each other across x = 50
+        assert!((points[0].x - 50.).abs() < 0.1, "{:?}", points);
+        assert!(first.distance(points[0]) < 0.1 && second.distance(points[0]) < 0.1);
+        let apart = second.translate(vec2(200., 0.));
+        assert!(first.intersect(&apart, 0.01).is_empty());
+    }
+
+    #[test]
+    fn identical_curves_meet_at_their_ends() {
+        let curve = arch();
+        let points = curve.intersect(&curve, 0.01);
+        assert_eq!(points.len(), 2, "{:?}", points);
+        let near =
+            |points: &[Vec2], point: Vec2| points.iter().any(|p| (*p - point).length() < 0.01);
+        assert!(
+            near(&points, curve.a) && near(&points, curve.c),
+            "{:?}",
+            points
+        );
+        // a straight curve running back over part of another one
+        let line = QuadCurve::new(vec2(0., 0.), vec2(50., 0.), vec2(100., 0.));
+        let back = QuadCurve::new(vec2(120., 0.), vec2(80., 0.), vec2(40., 0.));
+        let points = line.intersect(&back, 0.01);
+        assert_eq!(points.len(), 2, "{:?}", points);
+        assert!(near(&points, vec2(40., 0.)) && near(&points, vec2(100., 0.)));
+    }
+
+    #[test]
+    fn figure_eight_crosses_once() {
+        let path = BezierPath::from_svg(
+            "M -100 50 Q 0 10 100 -50 Q 150 0 100 50 Q 0 -10 -100 -50 Q -150 0 -100 50 Z",
+        )
+        .unwrap();
+        assert_eq!(path.curves.len(), 4);
+        let crossings = path.self_intersections();
+        // neighbours meet at their shared ends, that's not reported
+        assert_eq!(crossings.len(), 1, "{:?}", crossings);
+        let (i, j, point) = crossings[0];
+        assert_eq!((i, j), (0, 2));
+        assert!(point.y.abs() < 0.1, "{:?}", point);
+    }
+
+    #[test]
+    fn path_doubling_back_reports_the_overlap_ends() {
+        let path = BezierPath::from_svg("M 0 0 L 100 0 L 0 0").unwrap();
+        let crossings = path.self_intersections();
+        assert_eq!(crossings.len(), 2, "{:?}", crossings);
+        for (&(i, j, point), end) in crossings.iter().zip(&[vec2(0., 0.), vec2(100., 0.)]) {
+            assert_eq!((i, j), (0, 1));
+            assert!((point - *end).length() < 0.01, "{:?}", crossings);
+        }
+        // turning back halfway over the first curve
+        let path = BezierPath::from_svg("M 0 0 L 100 0 L 40 0").unwrap();
+        let crossings = path.self_intersections();
+        assert_eq!(crossings.len(), 2, "{:?}", crossings);
+        assert!(crossings.iter().all(|&(i, j, _)| (i, j) == (0, 1)));
+        assert!(crossings
+            .iter()
+            .any(|(_, _, p)| (*p - vec2(40., 0.)).length() < 0.01));
+    }
+
+    #[test]
+    fn vertices_carry_path_color() {
+        let red = Vec4::new(1., 0., 0., 1.);
+        let blue = Vec4::new(0., 0., 1., 0.5);
+        for &color in &[red, blue] {
+            let mut path = path_of(vec![arch(), arch().translate(vec2(100., 0.))]);
+            path.color = color;
+            let (vertices, _) = path.vertices(4.);
+            assert!(vertices
+                .iter()
+                .all(|v| v.color == color && v.end_color == color));
+        }
+    }
+
+    fn quad_bounds(vertices: &[Vertex]) -> (Vec2, Vec2) {
+        vertices.iter().fold(
+            (Vec2::splat(f32::INFINITY), Vec2::splat(f32::NEG_INFINITY)),
+            |(mi, ma), v| (mi.min(v.position), ma.max(v.position)),
+        )
+    }
+
+    #[test]
+    fn tapered_box_grows_with_max_width() {
+        let curve = arch();
+        let (thin, _) = curve.vertices_tapered(2., 2.);
+        let (tapered, _) = curve.vertices_tapered(2., 12.);
+        assert_eq!((tapered[0].thickness, tapered[0].end_thickness), (2., 12.));
+        let (thin_mi, thin_ma) = quad_bounds(&thin);
+        let (mi, ma) = quad_bounds(&tapered);
+        // padded by the larger width on every side
+        assert!(((thin_mi - mi) - Vec2::splat(10.)).abs().max_element() < 1e-3);
+        assert!(((ma - thin_ma) - Vec2::splat(10.)).abs().max_element() < 1e-3);
+        let (reversed, _) = curve.vertices_tapered(12., 2.);
+        assert_eq!(quad_bounds(&reversed), (mi, ma));
+    }
+
+    #[test]
+    fn square_cap_box_reaches_past_the_end() {
+        let width = 5.;
+        let mut path = path_of(vec![QuadCurve::new(
+            vec2(0., 0.),
+            vec2(50., 0.),
+            vec2(100., 0.),
+        )]);
+        path.cap = CapStyle::Square;
+        let (vertices, _) = path.vertices(width);
+        assert_eq!(vertices[0].caps, vec2(2., 2.));
+        let (mi, ma) = quad_bounds(&vertices);
+        // the corners of the square are a width away from the end along both axes
+        let corner = vec2(100., 0.) + vec2(width, width);
+        assert!(ma.x > corner.x && ma.y > corner.y, "{:?}", ma);
+        assert!(mi.x < -width && mi.y < -width, "{:?}", mi);
+
+        path.cap = CapStyle::Butt;
+        let (vertices, _) = path.vertices(width);
+        assert_eq!(vertices[0].caps, vec2(1., 1.));
+        assert!(quad_bounds(&vertices).1.x < ma.x);
+    }
+
+    #[test]
+    fn dash_pattern_and_offsets() {
+        assert_eq!(dash_vec(&[10., 10.]), Vec4::new(10., 10., 0., 0.));
+        // odd patterns repeat like in svg
+        assert_eq!(dash_vec(&[3.]), Vec4::new(3., 3., 0., 0.));
+        assert_eq!(dash_vec(&[0., 0.]), Vec4::zero());
+
+        let line = QuadCurve::new(vec2(0., 0.), vec2(15., 0.), vec2(30., 0.));
+        let mut path = path_of(vec![line, line.translate(vec2(30., 0.))]);
+        path.dash_pattern = Some(vec![10., 10.]);
+        let (vertices, _) = path.vertices(2.);
+        assert!(vertices
+            .iter()
+            .all(|v| v.dash == Vec4::new(10., 10., 0., 0.)));
+        // the second curve continues the pattern where the first one ended
+        assert_eq!(vertices[0].dash_offset, 0.);
+        assert!((vertices[4].dash_offset - 30.).abs() < 1e-3);
+    }
+
+    #[test]
+    fn clear_leaves_nothing_to_draw() {
+        let mut path = stroked(&[vec2(0., 0.), vec2(5., 5.), vec2(10., 0.), vec2(15., 5.)]);
+        path.close();
+        path.undo();
+        path.clear();
+        assert!(path.curves.is_empty());
+        assert_eq!((path.last, path.control, path.closed), (None, None, false));
+        let (vertices, indices) = path.vertices(3.);
+        assert!(vertices.is_empty() && indices.is_empty());
+        path.redo();
+        assert!(path.curves.is_empty());
+    }
+
+    #[test]
+    fn next_point_after_undo_replaces_the_end() {
+        let mut path = stroked(&[vec2(0., 0.), vec2(5., 5.), vec2(10., 0.), vec2(15., 5.)]);
+        let first = path.curves[0];
+        // start and control point of the curve are pending again, the pending control goes
+        path.undo();
+        assert!(path.curves.is_empty());
+        assert_eq!(
+            (path.last, path.control),
+            (Some(first.a), Some(first.control))
+        );
+        assert_eq!(path.stroke(vec2(6., 0.)), StrokeStep::Curve);
+        assert_eq!(
+            path.curves,
+            vec![QuadCurve::new(first.a, first.control, vec2(6., 0.))]
+        );
+
+        path.undo();
+        path.redo();
+        assert_eq!(path.curves.len(), 1);
+        assert_eq!(path.stroke(vec2(8., 3.)), StrokeStep::Control);
+        // undo on an empty path does nothing
+        let mut empty = BezierPath::default();
+        empty.undo();
+        assert_eq!((empty.last, empty.curves.len()), (None, 0));
+    }
+
+    #[test]
+    fn cubic_to_quads_within_tolerance() {
+        let cubic = CubicCurve::new(
+            vec2(0., 0.),
+            vec2(0., 100.),
+            vec2(100., -100.),
+            vec2(100., 0.),
+        );
+        let tolerance = 0.1;
+        let quads = cubic.to_quads(tolerance);
+        assert!(quads.len() > 1);
+        assert_eq!(quads[0].a, cubic.a);
+        assert_eq!(quads[quads.len() - 1].c, cubic.b);
+        assert!(quads.windows(2).all(|pair| pair[0].c == pair[1].a));
+        for i in 0..=200 {
+            let point = cubic.point_at(i as f32 / 200.);
+            let dist = quads
+                .iter()
+                .map(|quad| quad.distance(point))
+                .fold(f32::INFINITY, f32::min);
+            assert!(dist <= tolerance, "{} at {:?}", dist, point);
+        }
+    }
+
+    #[test]
+    fn offset_points_keep_their_distance() {
+        let curve = arch();
+        for &distance in &[10., -10.] {
+            let points = curve.offset(distance, 0.05);
+            assert!(points.len() > 2);
+            for point in points.iter() {
+                assert!(
+                    (curve.distance(*point) - distance.abs()).abs() < 0.05,
+                    "{:?} at {}",
+                    point,
+                    curve.distance(*point)
+                );
+            }
+        }
+        // the top of the arch has radius 25, the inner side folds over there
+        assert!(curve.offset(-30., 0.05).into_iter().all(finite));
+        let cusp = QuadCurve::new(vec2(0., 0.), vec2(10., 0.), vec2(5., 0.));
+        assert!(cusp.offset(3., 0.05).into_iter().all(finite));
+    }
+
+    #[test]
+    fn fit_reproduces_a_sampled_curve() {
+        let curve = arch();
+        let points: Vec<Vec2> = (0..=30).map(|i| curve.point_at(i as f32 / 30.)).collect();
+        let path = BezierPath::fit(&points, 0.1);
+        assert_eq!(path.curves.len(), 1);
+        assert!(
+            path.curves[0].approx_eq(&curve, 0.1),
+            "{:?}",
+            path.curves[0]
+        );
+        assert_eq!(path.last, Some(curve.c));
+
+        // two curves meeting at a corner need two pieces
+        let mut points = points;
+        let second = QuadCurve::new(curve.c, vec2(150., -100.), vec2(200., 0.));
+        points.extend((1..=30).map(|i| second.point_at(i as f32 / 30.)));
+        let path = BezierPath::fit(&points, 0.1);
+        assert!(path.curves.len() >= 2);
+        for point in points {
+            let dist = path
+                .curves
+                .iter()
+                .map(|curve| curve.distance(point))
+                .fold(f32::INFINITY, f32::min);
+            assert!(dist <= 0.1, "{} at {:?}", dist, point);
+        }
+    }
+
+    #[test]
+    fn simplify_collapses_a_nearly_straight_path() {
+        let points: Vec<Vec2> = (0..=100)
+            .map(|i| vec2(i as f32, 0.01 * (i as f32 * 0.7).sin()))
+            .collect();
+        let mut path = BezierPath::fit(&points, 0.001);
+        assert!(path.curves.len() > 2);
+        let (start, end) = (path.curves[0].a, path.curves[path.curves.len() - 1].c);
+        let mut undone = path.clone();
+        path.simplify(0.1);
+        assert!(path.curves.len() <= 2, "{:?}", path.curves);
+        assert_eq!(path.curves[0].a, start);
+        assert_eq!(path.curves[path.curves.len() - 1].c, end);
+
+        // the undone curve belongs to the path before simplification
+        undone.undo();
+        undone.simplify(0.1);
+        let simplified = undone.curves.clone();
+        undone.redo();
+        assert_eq!(undone.curves, simplified);
+    }
+
+    #[test]
+    fn extrema_of_symmetric_arch() {
+        let curve = arch();
+        assert_eq!(curve.extrema(), vec![0.5]);
+        // turning clockwise, tightest at the top
+        let top = curve.curvature_at(0.5);
+        assert!(top < 0.);
+        assert!((top + 0.04).abs() < 1e-6);
+        assert!(curve.curvature_at(0.1).abs() < top.abs());
+        let line = QuadCurve::new(vec2(0., 0.), vec2(1., 1.), vec2(2., 2.));
+        assert!(line.extrema().is_empty());
+        assert_eq!(line.curvature_at(0.3), 0.);
+    }
+
+    #[test]
+    fn reverse_twice_is_identity() {
+        let curve = arch();
+        assert_eq!(curve.reverse().point_at(0.25), curve.point_at(0.75));
+        assert_eq!(curve.reverse().reverse(), curve);
+
+        let mut path = stroked(&[
+            vec2(0., 0.),
+            vec2(5., 5.),
+            vec2(10., 0.),
+            vec2(15., -5.),
+            vec2(20., 0.),
+        ]);
+        let original = path.curves.clone();
+        path.reverse();
+        assert_eq!(path.curves[0].a, vec2(20., 0.));
+        assert_eq!(path.last, Some(vec2(0., 0.)));
+        path.reverse();
+        assert_eq!(path.curves, original);
+        assert_eq!(path.last, Some(vec2(20., 0.)));
+    }
+
+    #[test]
+    fn append_keeps_geometry() {
+        let first = stroked(&[vec2(0., 0.), vec2(5., 5.), vec2(10., 0.)]);
+        let second = stroked(&[
+            vec2(20., 0.),
+            vec2(25., -5.),
+            vec2(30., 0.),
+            vec2(35., 5.),
+            vec2(40., 0.),
+        ]);
+        let mut joined = first.clone();
+        joined.append(&second, false);
+        assert_eq!(joined.curves.len(), 3);
+        assert_eq!(joined.curves[..1], first.curves[..]);
+        assert_eq!(joined.curves[1..], second.curves[..]);
+        assert_eq!(joined.last, Some(vec2(40., 0.)));
+
+        let mut connected = first.clone();
+        connected.append(&second, true);
+        assert_eq!(connected.curves.len(), 4);
+        assert_eq!(connected.curves[1].a, vec2(10., 0.));
+        assert_eq!(connected.curves[1].c, vec2(20., 0.));
+        assert_eq!(connected.curves[2..], second.curves[..]);
+    }
+
+    #[test]
+    fn insert_and_remove_curves() {
+        let mut path = stroked(&[
+            vec2(0., 0.),
+            vec2(5., 5.),
+            vec2(10., 0.),
+            vec2(25., 5.),
+            vec2(30., 0.),
+        ]);
+        let middle = QuadCurve::new(vec2(10., 0.), vec2(15., -5.), vec2(20., 0.));
+        path.insert_curve(1, middle);
+        assert_eq!(path.curves.len(), 3);
+        assert_eq!(path.curves[1], middle);
+        assert_eq!(path.last, Some(vec2(30., 0.)));
+        assert_eq!(path.remove_curve(1), Some(middle));
+        assert_eq!(path.remove_curve(2), None);
+        // removing the last curve continues the path from its start
+        let last = path.curves[1];
+        assert_eq!(path.remove_curve(1), Some(last));
+        assert_eq!(path.last, Some(last.a));
+    }
+
+    #[test]
+    #[should_panic]
+    fn insert_past_the_end_panics() {
+        let mut path = path_of(vec![arch()]);
+        path.insert_curve(2, arch());
+    }
+
+    #[test]
+    fn samples_start_and_end_on_the_path() {
+        let path = path_of(vec![arch(), arch().translate(vec2(100., 0.))]);
+        let samples: Vec<(usize, f32, Vec2)> = path.iter_samples(5).collect();
+        assert_eq!(samples.len(), 10);
+        assert_eq!(samples[0], (0, 0., vec2(0., 0.)));
+        assert_eq!(samples[4], (0, 1., vec2(100., 0.)));
+        assert_eq!(samples[5], (1, 0., vec2(100., 0.)));
+        assert_eq!(samples[9], (1, 1., vec2(200., 0.)));
+        assert_eq!(samples[2].2, vec2(50., 50.));
+    }
+
+    #[test]
+    fn smooth_and_corner_joins() {
+        // the control points around the join are mirrored through it
+        let smooth = BezierPath::from_svg("M 0 0 Q 5 5 10 5 Q 15 5 20 0").unwrap();
+        assert_eq!(smooth.continuity(), vec![Continuity::Tangent]);
+        let corner = BezierPath::from_svg("M 0 0 Q 5 5 10 5 Q 15 4 20 0").unwrap();
+        assert_eq!(corner.continuity(), vec![Continuity::Position]);
+        // the tangents differ by about 0.2 radians
+        assert_eq!(corner.continuity_within(0.3), vec![Continuity::Tangent]);
+        let apart = BezierPath::from_svg("M 0 0 Q 5 5 10 5 L 12 5").unwrap();
+        let mut gap = path_of(vec![
+            apart.curves[0],
+            apart.curves[1].translate(vec2(1., 0.)),
+        ]);
+        assert_eq!(gap.continuity(), vec![Continuity::None]);
+        gap.closed = true;
+        assert_eq!(gap.continuity().len(), 2);
+    }
+
+    #[test]
+    fn smooth_makes_joins_tangent() {
+        let mut path =
+            BezierPath::from_svg("M 0 0 Q 5 10 10 0 Q 20 5 30 0 Q 35 -10 40 0 Z").unwrap();
+        assert!(path
+            .continuity()
+            .iter()
+            .all(|join| *join == Continuity::Position));
+        path.smooth(1.);
+        assert!(
+            path.continuity()
+                .iter()
+                .all(|join| *join == Continuity::Tangent),
+            "{:?}",
+            path.continuity()
+        );
+        // open ends stay where they were
+        let mut open = BezierPath::from_svg("M 0 0 Q 5 10 10 0 Q 20 5 30 0").unwrap();
+        open.smooth(1.);
+        assert_eq!(open.continuity(), vec![Continuity::Tangent]);
+        assert_eq!(
+            (open.curves[0].a, open.curves[1].c),
+            (vec2(0., 0.), vec2(30., 0.))
+        );
+        assert_eq!(open.last, Some(vec2(30., 0.)));
+    }
+
+    #[test]
+    fn bounding_box_of_straight_curve() {
+        let (a, c) = (vec2(-3., 4.), vec2(7., -6.));
+        let curve = QuadCurve::new(a, (a + c) / 2., c);
+        assert_eq!(curve.bounding_box(), (vec2(-3., -6.), vec2(7., 4.)));
+        // flat on one axis only
+        let curve = QuadCurve::new(vec2(0., 0.), vec2(5., 4.), vec2(10., 0.));
+        assert_eq!(curve.bounding_box(), (vec2(0., 0.), vec2(10., 2.)));
+    }
+
+    #[test]
+    fn subdivided_pieces_trace_the_curve() {
+        let curve = arch();
+        let pieces = curve.subdivide(5);
+        assert_eq!(pieces.len(), 5);
+        assert!(curve.subdivide(0).is_empty());
+        assert_eq!((pieces[0].a, pieces[4].c), (curve.a, curve.c));
+        for (i, piece) in pieces.iter().enumerate() {
+            for &u in &[0., 0.5, 1.] {
+                let t = (i as f32 + u) / 5.;
+                assert!(
+                    (piece.point_at(u) - curve.point_at(t)).length() < 1e-3,
+                    "t {}",
+                    t
+                );
+            }
+        }
+        assert!(pieces.windows(2).all(|pair| pair[0].c == pair[1].a));
+    }
+
+    #[test]
+    fn pieces_of_equal_length() {
+        let curve = QuadCurve::new(vec2(0., 0.), vec2(100., 0.), vec2(100., 100.));
+        let pieces = curve.subdivide_by_length(7);
+        assert_eq!(pieces.len(), 7);
+        let expected = curve.length() / 7.;
+        for piece in pieces.iter() {
+            assert!(
+                (piece.length() - expected).abs() < 0.01 * expected,
+                "{}",
+                piece.length()
+            );
+        }
+        assert_eq!(pieces[6].c, curve.c);
+    }
+
+    #[test]
+    fn array_round_trip() {
+        let points = [vec2(1., 2.), vec2(3., 4.), vec2(5., 6.)];
+        let curve = QuadCurve::from_array(points);
+        assert_eq!(curve, QuadCurve::new(points[0], points[1], points[2]));
+        assert_eq!(curve.as_array(), points);
+        assert_eq!(QuadCurve::from(points), curve);
+    }
+
+    #[test]
+    fn hit_test_picks_the_nearest_curve() {
+        let path = path_of(vec![
+            QuadCurve::new(vec2(0., 0.), vec2(50., 0.), vec2(100., 0.)),
+            QuadCurve::new(vec2(0., 10.), vec2(50., 10.), vec2(100., 10.)),
+        ]);
+        let (index, t) = path.hit_test(vec2(25., 7.), 5.).unwrap();
+        assert_eq!(index, 1);
+        assert!((t - 0.25).abs() < 1e-3);
+        assert_eq!(path.hit_test(vec2(25., 3.), 5.).unwrap().0, 0);
+        // equally near both, the first one wins
+        assert_eq!(path.hit_test(vec2(50., 5.), 5.).unwrap().0, 0);
+        assert_eq!(path.hit_test(vec2(25., 20.), 5.), None);
+        assert_eq!(BezierPath::default().hit_test(vec2(0., 0.), 5.), None);
+    }
+
+    #[test]
+    fn handle_at_grabs_the_nearest_point() {
+        let path = path_of(vec![
+            QuadCurve::new(vec2(0., 0.), vec2(10., 20.), vec2(20., 0.)),
+            QuadCurve::new(vec2(20., 0.), vec2(30., -20.), vec2(40., 0.)),
+        ]);
+        assert_eq!(path.handle_at(vec2(11., 19.), 3.), Some(Handle::Control(0)));
+        assert_eq!(path.handle_at(vec2(1., 1.), 3.), Some(Handle::Start(0)));
+        assert_eq!(path.handle_at(vec2(39., 0.), 3.), Some(Handle::End(1)));
+        // the join is the end of the first curve and the start of the second
+        assert_eq!(path.handle_at(vec2(20., 1.), 3.), Some(Handle::End(0)));
+        assert_eq!(path.handle_at(vec2(10., 0.), 3.), None);
+        // nearer handles win over earlier ones
+        assert_eq!(
+            path.handle_at(vec2(27., -17.), 20.),
+            Some(Handle::Control(1))
+        );
+    }
+
+    #[test]
+    fn nearest_on_path_counts_previous_curves() {
+        let path = path_of(vec![
+            QuadCurve::new(vec2(0., 0.), vec2(50., 0.), vec2(100., 0.)),
+            QuadCurve::new(vec2(100., 0.), vec2(100., 30.), vec2(100., 60.)),
+        ]);
+        let (distance, point) = path.nearest_on_path(vec2(110., 30.)).unwrap();
+        assert!((distance - 130.).abs() < 1e-2, "{}", distance);
+        assert!((point - vec2(100., 30.)).length() < 1e-3);
+        assert_eq!(BezierPath::default().nearest_on_path(vec2(0., 0.)), None);
+    }
+
+    #[test]
+    fn antialiased_quad_covers_the_faded_edge() {
+        let path = path_of(vec![QuadCurve::new(
+            vec2(0., 0.),
+            vec2(20., 200.),
+            vec2(40., 0.),
+        )]);
+        let (width, aa_width) = (20., 6.);
+        let (vertices, _) = path.vertices_antialiased(width, width, aa_width);
+        let quad: Vec<Vec2> = vertices.iter().map(|v| v.position).collect();
+        let inside = |p: Vec2| {
+            let sides: Vec<f32> = (0..4)
+                .map(|i| wedge(quad[(i + 1) % 4] - quad[i], p - quad[i]))
+                .collect();
+            sides.iter().all(|&s| s >= 0.) || sides.iter().all(|&s| s <= 0.)
+        };
+        // anything the shader doesn't discard, round ends included
+        let reach = width + aa_margin(aa_width) - 1e-2;
+        let curve = path.curves[0];
+        for i in 0..=32 {
+            let on_curve = curve.point_at(i as f32 / 32.);
+            for k in 0..32 {
+                let angle = k as f32 / 32. * std::f32::consts::TAU;
+                let p = on_curve + vec2(angle.cos(), angle.sin()) * reach;
+                assert!(inside(p), "{:?} clipped", p);
+            }
+        }
+    }
+
+    #[test]
+    fn degenerate_curves() {
+        let p = vec2(3., 4.);
+        assert!(QuadCurve::new(p, p, p).is_degenerate(DEGENERATE_EPSILON));
+        let tiny = vec2(1e-5, 0.);
+        assert!(QuadCurve::new(p, p + tiny, p - tiny).is_degenerate(DEGENERATE_EPSILON));
+        assert!(!arch().is_degenerate(DEGENERATE_EPSILON));
+        // closed loops have extent even though they end where they start
+        assert!(!QuadCurve::new(p, p + vec2(10., 0.), p).is_degenerate(DEGENERATE_EPSILON));
+    }
+
+    #[test]
+    fn param_at_distance_on_second_curve() {
+        let path = path_of(vec![
+            QuadCurve::new(vec2(0., 0.), vec2(50., 0.), vec2(100., 0.)),
+            QuadCurve::new(vec2(100., 0.), vec2(100., 50.), vec2(100., 100.)),
+        ]);
+        let (index, t) = path.param_at_distance(125.).unwrap();
+        assert_eq!(index, 1);
+        assert!((t - 0.25).abs() < 1e-3, "{}", t);
+        assert_eq!(path.param_at_distance(-5.), Some((0, 0.)));
+        assert_eq!(path.param_at_distance(1000.), Some((1, 1.)));
+        assert_eq!(BezierPath::default().param_at_distance(10.), None);
+    }
+
+    #[test]
+    fn gradient_increases_along_the_path() {
+        let mut path = stroked(&[
+            vec2(0., 0.),
+            vec2(10., 10.),
+            vec2(20., 0.),
+            vec2(30., -10.),
+            vec2(40., 0.),
+            vec2(60., 30.),
+            vec2(80., 0.),
+        ]);
+        path.gradient = Some((Vec4::zero(), Vec4::one()));
+        let (vertices, _) = path.vertices(4.);
+        let fractions: Vec<(f32, f32)> = vertices
+            .chunks(4)
+            .map(|quad| (quad[0].color.x, quad[0].end_color.x))
+            .collect();
+        assert_eq!(fractions.first().unwrap().0, 0.);
+        assert!((fractions.last().unwrap().1 - 1.).abs() < 1e-5);
+        for (i, &(start, end)) in fractions.iter().enumerate() {
+            assert!(start <= end, "quad {}", i);
+            if i > 0 {
+                assert!((start - fractions[i - 1].1).abs() < 1e-5, "quad {}", i);
+            }
+        }
+    }
+
+    #[test]
+    fn stroke_steps() {
+        let mut path = BezierPath::default();
+        assert_eq!(path.stroke(vec2(0., 0.)), StrokeStep::Start);
+        assert_eq!(path.stroke(vec2(5., 5.)), StrokeStep::Control);
+        assert_eq!(path.stroke(vec2(10., 0.)), StrokeStep::Curve);
+        assert_eq!(path.curves.len(), 1);
+        // the end of a curve starts the next one, the following point is its control
+        assert_eq!(path.stroke(vec2(15., -5.)), StrokeStep::Control);
+        assert_eq!(path.stroke(vec2(20., 0.)), StrokeStep::Curve);
+        assert_eq!(path.curves.len(), 2);
+    }
+
+    #[test]
+    fn preview_needs_start_and_control() {
+        let cursor = vec2(10., 0.);
+        let mut path = BezierPath::default();
+        assert_eq!(path.preview_curve(cursor), None);
+        path.stroke(vec2(0., 0.));
+        assert_eq!(path.preview_curve(cursor), None);
+        path.stroke(vec2(5., 5.));
+        let preview = QuadCurve::new(vec2(0., 0.), vec2(5., 5.), cursor);
+        assert_eq!(path.preview_curve(cursor), Some(preview));
+        assert!(path.curves.is_empty(), "previewing commits nothing");
+        path.stroke(cursor);
+        assert_eq!(path.preview_curve(cursor), None);
+    }
+
+    #[test]
+    fn round_joins_at_interior_corners_only() {
+        // a zigzag of three curves meeting at two corners
+        let mut path = path_of(vec![
+            QuadCurve::new(vec2(0., 0.), vec2(5., 0.), vec2(10., 0.)),
+            QuadCurve::new(vec2(10., 0.), vec2(10., 5.), vec2(10., 10.)),
+            QuadCurve::new(vec2(10., 10.), vec2(15., 10.), vec2(20., 10.)),
+        ]);
+        assert_eq!(path.vertices(2.).0.len(), 3 * 4);
+        path.join = JoinStyle::Round;
+        let (vertices, indices) = path.vertices(2.);
+        assert_eq!(vertices.len(), 5 * 4);
+        assert_eq!(indices.len(), 5 * 6);
+        let joints: Vec<Vec2> = vertices
+            .chunks(4)
+            .map(|quad| quad[0].curve)
+            .filter(|curve| curve.a == curve.c)
+            .map(|curve| curve.a)
+            .collect();
+        assert_eq!(joints, vec![vec2(10., 0.), vec2(10., 10.)]);
+        // smooth joins need nothing to fill
+        let smooth = BezierPath {
+            join: JoinStyle::Round,
+            ..path_of(vec![
+                QuadCurve::new(vec2(0., 0.), vec2(5., 5.), vec2(10., 0.)),
+                QuadCurve::new(vec2(10., 0.), vec2(15., -5.), vec2(20., 0.)),
+            ])
+        };
+        assert_eq!(smooth.vertices(2.).0.len(), 2 * 4);
+    }
+
+    /// the simplest curve there is, to check `flatten` needs nothing quadratic
+    struct Segment(Vec2, Vec2);
+
+    impl Curve for Segment {
+        fn point_at(&self, t: f32) -> Vec2 {
+            self.0.lerp(self.1, t)
+        }
+        fn tangent_at(&self, _t: f32) -> Vec2 {
+            self.1 - self.0
+        }
+        fn split_at(&self, t: f32) -> (Self, Self) {
+            let middle = self.point_at(t);
+            (Segment(self.0, middle), Segment(middle, self.1))
+        }
+        fn flatness(&self) -> f32 {
+            0.
+        }
+    }
+
+    #[test]
+    fn flatten_any_curve() {
+        let segment = Segment(vec2(0., 0.), vec2(3., 4.));
+        assert_eq!(flatten(&segment, 0.1), vec![vec2(0., 0.), vec2(3., 4.)]);
+        let curve = arch();
+        assert_eq!(flatten(&curve, 0.5), curve.flatten(0.5));
+        assert_eq!(
+            Curve::point_at(&curve, 0.5),
+            QuadCurve::point_at(&curve, 0.5)
+        );
+    }
+
+    #[test]
+    fn split_curve_keeps_the_outline() {
+        let original = path_of(vec![
+            arch(),
+            QuadCurve::new(vec2(100., 0.), vec2(150., -50.), vec2(200., 0.)),
+        ]);
+        let mut path = original.clone();
+        assert!(path.split_curve(1, 0.3));
+        assert_eq!(path.curves.len(), 3);
+        assert_eq!(path.curves[1].c, path.curves[2].a);
+        for i in 0..=20 {
+            let t = i as f32 / 20.;
+            let p = original.curves[1].point_at(t);
+            let nearest = path.curves[1].distance(p).min(path.curves[2].distance(p));
+            assert!(nearest < 1e-3, "t {} off by {}", t, nearest);
+        }
+        assert!(!path.split_curve(3, 0.5));
+        assert!(!path.split_curve(0, 0.));
+        assert!(!path.split_curve(0, 1.));
+        assert_eq!(path.curves.len(), 3);
+    }
+
+    #[test]
+    fn halves_join_back_into_the_curve() {
+        let curve = arch();
+        let (left, right) = curve.split_at(0.4);
+        let mut path = path_of(vec![left, right]);
+        let deviation = path.join_curves(0).unwrap();
+        assert!(deviation < 0.1, "{}", deviation);
+        assert_eq!(path.curves.len(), 1);
+        assert!(
+            path.curves[0].approx_eq(&curve, 0.5),
+            "{:?}",
+            path.curves[0]
+        );
+        assert_eq!(path.join_curves(0), None);
+        // an s shape isn't one quadratic curve, a tight tolerance keeps it
+        let mut s_shape = path_of(vec![
+            arch(),
+            QuadCurve::new(vec2(100., 0.), vec2(150., -100.), vec2(200., 0.)),
+        ]);
+        let deviation = s_shape.join_curves_within(0, 1.).unwrap();
+        assert!(deviation > 1.);
+        assert_eq!(s_shape.curves.len(), 2);
+        assert!(s_shape.join_curves_within(0, deviation + 1.).is_some());
+        assert_eq!(s_shape.curves.len(), 1);
+    }
+
+    #[cfg(feature = "rayon")]
+    #[test]
+    fn parallel_vertices_match_serial() {
+        fn bytes(vertices: &[Vertex]) -> &[u8] {
+            // Vertex is repr(C) of f32s only, no padding
+            unsafe {
+                std::slice::from_raw_parts(
+                    vertices.as_ptr() as *const u8,
+                    std::mem::size_of_val(vertices),
+                )
+            }
+        }
+        let points: Vec<Vec2> = (0..301)
+            .map(|i| vec2(i as f32 * 3., ((i * 37) % 11) as f32 * 4.))
+            .collect();
+        let mut path = stroked(&points);
+        for configure in [
+            |_: &mut BezierPath| {},
+            |path: &mut BezierPath| path.join = JoinStyle::Round,
+            |path: &mut BezierPath| {
+                path.gradient = Some((Vec4::zero(), Vec4::one()));
+                path.dash_pattern = Some(vec![5., 3.]);
+            },
+        ] {
+            configure(&mut path);
+            let (serial, serial_indices) = path.vertices(3.);
+            let (parallel, parallel_indices) = path.vertices_parallel(3.);
+            assert_eq!(bytes(&serial), bytes(&parallel));
+            assert_eq!(serial_indices, parallel_indices);
+        }
+    }
+
+    #[test]
+    fn catmull_rom_passes_through_the_points() {
+        let points = [
+            vec2(0., 0.),
+            vec2(20., 30.),
+            vec2(45., 10.),
+            vec2(60., 40.),
+            vec2(90., 0.),
+        ];
+        for &tension in &[0., 0.5, 1.] {
+            let path = BezierPath::from_catmull_rom(&points, tension);
+            assert_eq!(path.curves.first().unwrap().a, points[0]);
+            assert_eq!(path.curves.last().unwrap().c, points[4]);
+            for &point in points.iter() {
+                let nearest = path.nearest_on_path(point).unwrap().1;
+                assert!((nearest - point).length() < 1e-3, "{:?} missed", point);
+            }
+            assert!(path.curves.windows(2).all(|pair| pair[0].c == pair[1].a));
+        }
+        assert!(BezierPath::from_catmull_rom(&points[..1], 0.)
+            .curves
+            .is_empty());
+    }
+
+    #[test]
+    fn oriented_box_encloses_the_curve() {
+        let curves = [
+            arch(),
+            QuadCurve::new(vec2(10., 10.), vec2(-40., 30.), vec2(25., -5.)),
+            // closed, the chord has no direction
+            QuadCurve::new(vec2(5., 5.), vec2(20., 10.), vec2(5., 5.)),
+        ];
+        for curve in curves.iter() {
+            let corners = curve.oriented_bounding_box(2.);
+            assert!(
+                corners.iter().all(|&corner| finite(corner)),
+                "{:?}",
+                corners
+            );
+            let inside = |p: Vec2| {
+                (0..4).all(|i| wedge(corners[(i + 1) % 4] - corners[i], p - corners[i]) <= 1e-3)
+            };
+            for i in 0..=50 {
+                let p = curve.point_at(i as f32 / 50.);
+                assert!(inside(p), "{:?} outside {:?}", p, corners);
+            }
+        }
+    }
+
+    #[test]
+    fn joint_widths_pad_by_the_larger_one() {
+        let path = path_of(vec![
+            QuadCurve::new(vec2(0., 0.), vec2(50., 0.), vec2(100., 0.)),
+            QuadCurve::new(vec2(100., 0.), vec2(150., 0.), vec2(200., 0.)),
+        ]);
+        let (vertices, _) = path.vertices_with_widths(&[2., 10., 4.], 0.);
+        let widths: Vec<(f32, f32)> = vertices
+            .chunks(4)
+            .map(|quad| (quad[0].thickness, quad[0].end_thickness))
+            .collect();
+        assert_eq!(widths, vec![(2., 10.), (10., 4.)]);
+        // both curves are flat, their boxes reach as far as the wider end
+        for quad in vertices.chunks(4) {
+            let (mi, ma) = quad_bounds(quad);
+            assert!((mi.y + 10.).abs() < 1e-3 && (ma.y - 10.).abs() < 1e-3);
+        }
+        // missing widths repeat the last one
+        let (vertices, _) = path.vertices_with_widths(&[3.], 0.);
+        assert!(vertices
+            .iter()
+            .all(|v| v.thickness == 3. && v.end_thickness == 3.));
+    }
+
+    /// arc of `radius` around `center` from angle `from` to `to` in `n` curves, controls
+    /// where the tangents of the ends of each piece meet
+    fn arc(center: Vec2, radius: f32, from: f32, to: f32, n: usize) -> Vec<QuadCurve> {
+        let step = (to - from) / n as f32;
+        let at = |angle: f32, r: f32| center + vec2(angle.cos(), angle.sin()) * r;
+        (0..n)
+            .map(|i| {
+                let start = from + step * i as f32;
+                let control = at(start + step / 2., radius / (step / 2.).cos());
+                QuadCurve::new(at(start, radius), control, at(start + step, radius))
+            })
+            .collect()
+    }
+
+    #[test]
+    fn semicircle_area() {
+        use std::f32::consts::PI;
+        let radius = 10.;
+        // counterclockwise over the top, the diameter closes it
+        let mut path = path_of(arc(Vec2::zero(), radius, 0., PI, 8));
+        let expected = PI * radius * radius / 2.;
+        let area = path.enclosed_area();
+        assert!((area - expected).abs() < 0.005 * expected, "{}", area);
+        path.reverse();
+        assert!((path.enclosed_area() + area).abs() < 1e-3);
+        let terms: f32 = path.curves.iter().map(QuadCurve::signed_area_term).sum();
+        // the diameter runs through the origin and adds nothing
+        assert!((terms + area).abs() < 1e-3);
+    }
+
+    #[test]
+    fn centroid_of_symmetric_paths() {
+        use std::f32::consts::PI;
+        let center = vec2(30., 20.);
+        let mut circle = path_of(arc(center, 10., 0., 2. * PI, 8));
+        let centroid = circle.centroid().unwrap();
+        assert!((centroid - center).length() < 1e-3, "{:?}", centroid);
+        // clockwise paths have a negative area but the same center
+        circle.reverse();
+        assert!((circle.centroid().unwrap() - center).length() < 1e-3);
+        // a semicircle is symmetric about the y axis, its centroid is 4r/3pi above the diameter
+        let semicircle = BezierPath {
+            closed: true,
+            ..path_of(arc(Vec2::zero(), 10., 0., PI, 8))
+        };
+        let centroid = semicircle.centroid().unwrap();
+        assert!(centroid.x.abs() < 1e-3);
+        assert!(
+            (centroid.y - 40. / (3. * PI)).abs() < 0.02,
+            "{:?}",
+            centroid
+        );
+        assert_eq!(path_of(arc(Vec2::zero(), 10., 0., PI, 8)).centroid(), None);
+    }
+
+    #[test]
+    fn quarter_turn_and_wedge() {
+        let p = vec2(3., 1.);
+        assert_eq!(math::rot(p, 0., 1.), vec2(-1., 3.));
+        let turned = math::rotate(p, std::f32::consts::FRAC_PI_2);
+        assert!((turned - vec2(-1., 3.)).length() < 1e-6, "{:?}", turned);
+        assert_eq!(math::wedge(vec2(2., 0.), vec2(1., 3.)), 6.);
+        assert_eq!(wedge(vec2(1., 3.), vec2(2., 0.)), -6.);
+        assert_eq!(wedge(p, p * 2.), 0.);
+    }
+
+    #[cfg(feature = "approx")]
+    #[test]
+    fn curves_differing_by_rounding_are_approx_equal() {
+        use approx::{assert_abs_diff_eq, assert_abs_diff_ne};
+        let curve = QuadCurve::new(vec2(0.5, 0.25), vec2(0.75, 1.), vec2(1., 0.5));
+        let nudge = vec2(1e-7, -1e-7);
+        let nudged = QuadCurve::new(curve.a + nudge, curve.control - nudge, curve.c + nudge);
+        assert_ne!(curve, nudged);
+        assert_abs_diff_eq!(curve, nudged);
+        assert_abs_diff_eq!(curve, nudged, epsilon = 1e-6);
+        assert_abs_diff_ne!(curve, arch());
+    }
+
+    #[test]
+    fn fit_transform_with_and_without_margin() {
+        let path = path_of(vec![QuadCurve::new(
+            vec2(0., 0.),
+            vec2(50., 25.),
+            vec2(100., 50.),
+        )]);
+        let screen = vec2(400., 300.);
+        // the width limits the scale, the content is centered vertically
+        let fit = path.fit_transform(screen, 0.);
+        assert!((fit.transform_point2(vec2(0., 0.)) - vec2(0., 50.)).length() < 1e-3);
+        assert!((fit.transform_point2(vec2(100., 50.)) - vec2(400., 250.)).length() < 1e-3);
+        let fit = path.fit_transform(screen, 20.);
+        assert!((fit.transform_point2(vec2(0., 0.)) - vec2(20., 60.)).length() < 1e-3);
+        assert!((fit.transform_point2(vec2(100., 50.)) - vec2(380., 240.)).length() < 1e-3);
+        assert_eq!(
+            fit_box(vec2(0., 0.), vec2(100., 50.), screen, 20.),
+            (3.6, vec2(20., 60.))
+        );
+        assert_eq!(
+            BezierPath::default().fit_transform(screen, 20.),
+            Mat3::identity()
+        );
+    }
+
+    #[test]
+    fn repeated_point_adds_nothing() {
+        let state = |path: &BezierPath| (path.last, path.control, path.curves.clone());
+        let mut path = BezierPath::default();
+        let p = vec2(10., 10.);
+        assert_eq!(path.stroke(p), StrokeStep::Start);
+        let after_first = state(&path);
+        assert_eq!(path.stroke(p), StrokeStep::Ignored);
+        assert_eq!(state(&path), after_first);
+        // a pending control point is protected the same way, also within the threshold
+        path.stroke(vec2(20., 0.));
+        let with_control = state(&path);
+        assert_eq!(path.stroke(vec2(20., 0.)), StrokeStep::Ignored);
+        assert_eq!(path.stroke(vec2(20., 5e-4)), StrokeStep::Ignored);
+        assert_eq!(state(&path), with_control);
+        // redo history survives an ignored point
+        path.stroke(vec2(30., 10.));
+        path.undo();
+        path.stroke(vec2(10., 10.));
+        assert_eq!(path.redo.len(), 1);
+        // the threshold is configurable
+        path.min_point_distance = 0.;
+        assert_eq!(path.stroke(vec2(10., 10.)), StrokeStep::Curve);
+    }
+
+    #[test]
+    fn point_at_distance_along_the_path() {
+        assert_eq!(BezierPath::default().point_at_distance(0.), None);
+        let path = path_of(vec![
+            QuadCurve::new(vec2(0., 0.), vec2(50., 0.), vec2(100., 0.)),
+            QuadCurve::new(vec2(100., 0.), vec2(100., 50.), vec2(100., 100.)),
+        ]);
+        let middle = path.point_at_distance(150.).unwrap();
+        assert!((middle - vec2(100., 50.)).length() < 1e-2, "{:?}", middle);
+        assert_eq!(
+            path.point_at_distance(path.length()),
+            Some(vec2(100., 100.))
+        );
+        assert_eq!(path.point_at_distance(path.length() + 1.), None);
+        assert_eq!(path.point_at_distance(-1.), None);
+        // lengths summed curve by curve round differently than `length`
+        let mut seed = 7u32;
+        let mut random = || {
+            seed = seed.wrapping_mul(1_103_515_245).wrapping_add(12_345);
+            (seed >> 8) as f32 / (1 << 24) as f32 * 100.
+        };
+        for _ in 0..200 {
+            let points: Vec<Vec2> = (0..41).map(|_| vec2(random(), random())).collect();
+            let path = stroked(&points);
+            let end = path.curves.last().unwrap().c;
+            let at_length = path.point_at_distance(path.length()).unwrap();
+            assert!(
+                (at_length - end).length() < 1e-2,
+                "{:?} {:?}",
+                at_length,
+                end
+            );
+        }
+    }
+
+    #[test]
+    fn close_back_to_the_start() {
+        let points = [
+            vec2(0., 0.),
+            vec2(10., 10.),
+            vec2(20., 0.),
+            vec2(30., -10.),
+            vec2(40., 0.),
+        ];
+        // the pending control point bends the closing curve
+        let mut path = stroked(&points);
+        path.stroke(vec2(20., -30.));
+        path.close();
+        assert!(path.closed);
+        assert_eq!(path.curves.len(), 3);
+        assert_eq!(
+            path.curves[2],
+            QuadCurve::new(vec2(40., 0.), vec2(20., -30.), vec2(0., 0.))
+        );
+        assert_eq!((path.last, path.control), (Some(vec2(0., 0.)), None));
+        let (vertices, indices) = path.vertices(2.);
+        assert_eq!((vertices.len(), indices.len()), (3 * 4, 3 * 6));
+        assert_eq!(vertices[8].curve, path.curves[2]);
+        // without one the path closes with a straight curve
+        let mut path = stroked(&points);
+        path.close();
+        assert_eq!(
+            path.curves[2],
+            QuadCurve::new(vec2(40., 0.), vec2(20., 0.), vec2(0., 0.))
+        );
+        path.close();
+        assert_eq!(path.curves.len(), 3, "closing twice adds nothing");
+        path.clear();
+        assert!(!path.closed);
+        // a single curve stays open
+        let mut single = stroked(&points[..3]);
+        single.close();
+        assert!(!single.closed);
+        assert_eq!(single.curves.len(), 1);
+    }
+
+    #[test]
+    fn instance_keeps_quad_corners_and_curve_data() {
+        let mut path = path_of(vec![arch(), arch().translate(vec2(100., 0.))]);
+        path.gradient = Some((Vec4::new(1., 0., 0., 1.), Vec4::new(0., 0., 1., 1.)));
+        path.dash_pattern = Some(vec![6., 2.]);
+        let (vertices, _) = path.vertices(4.);
+        for quad in vertices.chunks(4) {
+            let instance = CurveInstance::from_quad(quad);
+            for (corner, vertex) in instance.corners.iter().zip(quad) {
+                assert_eq!(*corner, vertex.position);
+            }
+            let vertex = quad[3];
+            assert_eq!(instance.curve, vertex.curve);
+            assert_eq!(
+                (instance.thickness, instance.end_thickness),
+                (vertex.thickness, vertex.end_thickness)
+            );
+            assert_eq!(
+                (instance.color, instance.end_color),
+                (vertex.color, vertex.end_color)
+            );
+            assert_eq!(instance.caps, vertex.caps);
+            assert_eq!(
+                (instance.dash_offset, instance.dash),
+                (vertex.dash_offset, vertex.dash)
+            );
+        }
+    }
+
+    #[test]
+    fn vertex_and_instance_are_packed_floats() {
+        // position, curve, thicknesses, colors, caps, dash offset and pattern
+        let vertex_floats = 2 + 6 + 2 + 8 + 2 + 1 + 4;
+        assert_eq!(std::mem::size_of::<Vertex>(), vertex_floats * 4);
+        // the instance has all 4 corners instead of one position
+        assert_eq!(
+            std::mem::size_of::<CurveInstance>(),
+            (vertex_floats - 2 + 8) * 4
+        );
     }
 }
@@ -1,58 +1,186 @@
+//! Quadratic bezier strokes drawn on the GPU with a distance field, and the geometry
+//! behind them
+//!
+//! ```
+//! use bezier::BezierPath;
+//! use glam::vec2;
+//!
+//! let mut path = BezierPath::default();
+//! for point in [vec2(0., 0.), vec2(50., 100.), vec2(100., 0.)] {
+//!     path.stroke(point);
+//! }
+//! // a quad of 4 vertices and 6 indices per curve
+//! let (vertices, indices) = path.vertices(10.);
+//! assert_eq!((vertices.len(), indices.len()), (4, 6));
+//! ```
+
+#[cfg(feature = "render")]
+pub mod bstroke;
+pub mod geometry;
+pub mod raster;
+pub mod shape;
+pub mod svg;
+
+#[cfg(feature = "render")]
+pub use bstroke::Strokes;
+pub use geometry::{BezierPath, QuadCurve};
+pub use shape::Shape;
+
+/// number of random curves in the tessellation benchmark
+pub const BENCH_STROKES_NUM: usize = 10;
+
+/// size of offscreen images, see `Strokes::export_png`
+pub const TEXTURE_WIDTH: u32 = 3840;
+pub const TEXTURE_HEIGHT: u32 = 2160;
+
+/// frames per second of an average frame taking `frame`, e.g. from `Timer::tick`
+pub fn frames_per_second(frame: std::time::Duration) -> f32 {
+    1e9 / frame.as_nanos() as f32
+}
+
 pub use timer::Timer;
 
-#[cfg(not(target_arch = "wasm32"))]
 mod timer {
-    use std::time::{Duration, Instant};
-    
+    use std::collections::VecDeque;
+    use std::time::Duration;
+
+    /// point in time frames are measured from, the clock is the only part of the timer
+    /// that differs between targets
+    #[cfg(not(target_arch = "wasm32"))]
+    #[derive(Clone, Copy)]
+    struct Timestamp(std::time::Instant);
+
+    /// `Instant` is unavailable on wasm32, seconds of miniquad's `performance.now()`
+    /// based clock are used there
+    #[cfg(target_arch = "wasm32")]
+    #[derive(Clone, Copy)]
+    struct Timestamp(f64);
+
+    impl Timestamp {
+        #[cfg(not(target_arch = "wasm32"))]
+        fn now() -> Timestamp {
+            Timestamp(std::time::Instant::now())
+        }
+
+        #[cfg(target_arch = "wasm32")]
+        fn now() -> Timestamp {
+            Timestamp(miniquad::date::now())
+        }
+
+        #[cfg(not(target_arch = "wasm32"))]
+        fn since(self, earlier: Timestamp) -> Duration {
+            self.0 - earlier.0
+        }
+
+        #[cfg(target_arch = "wasm32")]
+        fn since(self, earlier: Timestamp) -> Duration {
+            Duration::from_secs_f64((self.0 - earlier.0).max(0.))
+        }
+    }
+
+    /// moving average of frame durations over the last `num_frames` ticks
     pub struct Timer {
         num_frames: usize,
-        timestamps: Vec<Duration>,
-        prev_time: Instant,
+        timestamps: VecDeque<Duration>,
+        prev_time: Timestamp,
     }
-    
+
     impl Timer {
         pub fn new(num_frames: usize) -> Self {
             Timer {
                 num_frames,
-                timestamps: Vec::with_capacity(num_frames),
-                prev_time: Instant::now(),
+                timestamps: VecDeque::with_capacity(num_frames + 1),
+                prev_time: Timestamp::now(),
             }
         }
-    
+
+        /// records the time since the previous tick, once the window is filled
+        /// returns the average of the most recent `num_frames` durations
         pub fn tick(&mut self) -> Option<Duration> {
-            let now = Instant::now();
-            self.timestamps.push(now - self.prev_time);
-            let res = if self.timestamps.len() > self.num_frames {
+            let now = Timestamp::now();
+            let frame = now.since(self.prev_time);
+            self.prev_time = now;
+            self.push(frame)
+        }
+
+        /// adds a frame of `frame` duration to the window, see `tick`
+        fn push(&mut self, frame: Duration) -> Option<Duration> {
+            self.timestamps.push_back(frame);
+            if self.timestamps.len() > self.num_frames {
+                self.timestamps.pop_front();
+            }
+            if self.num_frames > 0 && self.timestamps.len() == self.num_frames {
                 Some(
-                    (self
-                        .timestamps
-                        .drain(..)
-                        .take(self.num_frames)
-                        .sum::<Duration>()
-                        / self.num_frames as u32)
+                    (self.timestamps.iter().sum::<Duration>() / self.num_frames as u32)
                         .max(Duration::from_nanos(1)),
                 )
             } else {
                 None
-            };
-            self.prev_time = now;
-            res
+            }
         }
-    }
-}
 
+        /// drops the collected durations and restarts measuring from now
+        pub fn reset(&mut self) {
+            self.timestamps.clear();
+            self.prev_time = Timestamp::now();
+        }
 
-#[cfg(target_arch = "wasm32")]
-mod timer {
-    pub struct Timer {}
+        /// shortest frame in the current window
+        pub fn min(&self) -> Option<Duration> {
+            self.timestamps.iter().min().copied()
+        }
 
-    impl Timer {
-        pub fn new(_: usize) -> Self {
-            Timer {}
+        /// longest frame in the current window
+        pub fn max(&self) -> Option<Duration> {
+            self.timestamps.iter().max().copied()
         }
-        pub fn tick(&mut self) -> Option<()> {
-            None
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        #[test]
+        fn moving_average_of_the_last_frames() {
+            let mut timer = Timer::new(3);
+            let ms = Duration::from_millis;
+            assert_eq!(timer.push(ms(10)), None);
+            assert_eq!(timer.push(ms(20)), None);
+            assert_eq!(timer.push(ms(30)), Some(ms(20)));
+            // the oldest frame drops out on every tick from now on
+            assert_eq!(timer.push(ms(40)), Some(ms(30)));
+            assert_eq!(timer.push(ms(80)), Some(ms(50)));
         }
+
+        #[test]
+        fn min_max_and_reset() {
+            let mut timer = Timer::new(3);
+            let ms = Duration::from_millis;
+            assert_eq!((timer.min(), timer.max()), (None, None));
+            for &frame in &[30, 10, 20, 40] {
+                timer.push(ms(frame));
+            }
+            // the 30ms frame left the window
+            assert_eq!((timer.min(), timer.max()), (Some(ms(10)), Some(ms(40))));
+            timer.reset();
+            assert_eq!((timer.min(), timer.max()), (None, None));
+            assert_eq!(timer.push(ms(10)), None);
         }
+    }
+}
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn fps_of_frame_durations() {
+        assert_eq!(frames_per_second(Duration::from_millis(20)), 50.);
+        assert_eq!(frames_per_second(Duration::from_secs(2)), 0.5);
+        let frame = Duration::from_nanos(16_666_667);
+        assert!((frames_per_second(frame) - 60.).abs() < 1e-3);
+        // the average `Timer::tick` gives is never zero
+        assert!(frames_per_second(Duration::from_nanos(1)).is_finite());
+    }
 }
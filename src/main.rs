@@ -1,30 +1,43 @@
-pub mod bstroke;
-pub mod geometry;
-
-pub const BENCH_STROKES_NUM: usize = 10;
-
 use miniquad::*;
 
-use bezier::Timer;
-
-use bstroke::Strokes;
+use bezier::{Strokes, Timer};
 
-#[cfg(target_arch = "x86_64")]
-pub const TEXTURE_WIDTH: u32 = 3840;
-#[cfg(target_arch = "x86_64")]
-pub const TEXTURE_HEIGHT: u32 = 2160;
+/// rgba background, gray
+pub const DEFAULT_CLEAR_COLOR: [f32; 4] = [0.5, 0.5, 0.5, 0.];
 
-struct Stage {
+pub struct Stage {
     strokes: Strokes,
+    clear_color: [f32; 4],
     _timer: Timer,
 }
 
 impl Stage {
-    pub fn new(ctx: &mut Context, max_curves_num: usize) -> Stage {
-        Stage {
-            strokes: Strokes::new(ctx, max_curves_num),
+    pub fn new(ctx: &mut Context, max_curves_num: usize) -> Result<Stage, ShaderError> {
+        Stage::with_clear_color(ctx, max_curves_num, DEFAULT_CLEAR_COLOR)
+    }
+
+    pub fn with_clear_color(
+        ctx: &mut Context,
+        max_curves_num: usize,
+        clear_color: [f32; 4],
+    ) -> Result<Stage, ShaderError> {
+        let mut strokes = Strokes::new(ctx, max_curves_num)?;
+        strokes.set_dpi_scale(ctx.dpi_scale());
+        Ok(Stage {
+            strokes,
+            clear_color,
             _timer: Timer::new(100),
-        }
+        })
+    }
+
+    pub fn set_clear_color(&mut self, clear_color: [f32; 4]) {
+        self.clear_color = clear_color;
+    }
+
+    /// png of the drawing on the clear color
+    #[cfg(feature = "image")]
+    pub fn export_png(&self, path: &std::path::Path, supersample: u32) -> std::io::Result<()> {
+        self.strokes.export_png(path, self.clear_color, supersample)
     }
 }
 
@@ -35,16 +48,39 @@ impl EventHandler for Stage {
         self.strokes.mouse_button_down_event(ctx, button, x, y);
     }
 
+    fn mouse_button_up_event(&mut self, ctx: &mut Context, button: MouseButton, x: f32, y: f32) {
+        self.strokes.mouse_button_up_event(ctx, button, x, y);
+    }
+
     fn mouse_motion_event(&mut self, ctx: &mut Context, x: f32, y: f32) {
         self.strokes.mouse_motion_event(ctx, x, y);
     }
 
+    fn key_down_event(
+        &mut self,
+        ctx: &mut Context,
+        keycode: KeyCode,
+        keymods: KeyMods,
+        repeat: bool,
+    ) {
+        self.strokes.key_down_event(ctx, keycode, keymods, repeat);
+    }
+
+    fn mouse_wheel_event(&mut self, ctx: &mut Context, x: f32, y: f32) {
+        self.strokes.mouse_wheel_event(ctx, x, y);
+    }
+
+    fn key_up_event(&mut self, ctx: &mut Context, keycode: KeyCode, keymods: KeyMods) {
+        self.strokes.key_up_event(ctx, keycode, keymods);
+    }
+
     fn update(&mut self, ctx: &mut Context) {
         self.strokes.update(ctx);
     }
 
     fn draw(&mut self, ctx: &mut Context) {
-        ctx.begin_default_pass(PassAction::clear_color(0.5, 0.5, 0.5, 0.));
+        let [r, g, b, a] = self.clear_color;
+        ctx.begin_default_pass(PassAction::clear_color(r, g, b, a));
         self.strokes.draw(ctx);
         ctx.end_render_pass();
         ctx.commit_frame();
@@ -52,7 +88,7 @@ impl EventHandler for Stage {
 }
 
 fn main() {
-    miniquad::start(conf::Conf::default(), |mut ctx| {
-        UserData::owning(Stage::new(&mut ctx, 1000), ctx)
+    miniquad::start(conf::Conf::default(), |ctx| {
+        Box::new(Stage::new(ctx, 1000).expect("stroke shaders failed to compile"))
     });
 }
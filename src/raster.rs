@@ -0,0 +1,207 @@
+use glam::{vec2, Vec4};
+
+use crate::geometry::*;
+
+fn smoothstep(edge0: f32, edge1: f32, x: f32) -> f32 {
+    let t = ((x - edge0) / (edge1 - edge0)).clamp(0., 1.);
+    t * t * (3. - 2. * t)
+}
+
+/// draws the path into a `width` x `height` rgba8 buffer, rows top to bottom, with the
+/// same coverage as the fragment shader: `stroke_width` is compared against
+/// `QuadCurve::distance` at pixel centers and the edge is smoothed over one pixel.
+/// Curves are blended over each other in order on a transparent background.
+/// Every curve gets round ends, cap styles and dashes are not rasterized
+pub fn render_to_buffer(path: &BezierPath, width: u32, height: u32, stroke_width: f32) -> Vec<u8> {
+    render_with_background(path, width, height, stroke_width, Vec4::zero())
+}
+
+/// `render_to_buffer` on top of a `background` rgba color instead of transparency
+pub fn render_with_background(
+    path: &BezierPath,
+    width: u32,
+    height: u32,
+    stroke_width: f32,
+    background: Vec4,
+) -> Vec<u8> {
+    let mut pixels = vec![background; (width * height) as usize];
+    draw_path(&mut pixels, width, height, path, stroke_width, 1);
+    to_rgba8(pixels)
+}
+
+/// paths with their stroke widths drawn in order over `background`, see `render_to_buffer`.
+/// Every pixel averages `supersample` x `supersample` samples (each with the edge smoothed
+/// over one sample) like a render at that many times the resolution box filtered down,
+/// which softens corners and thin features. 0 and 1 take a single sample
+pub fn render_paths(
+    paths: &[(BezierPath, f32)],
+    width: u32,
+    height: u32,
+    background: Vec4,
+    supersample: u32,
+) -> Vec<u8> {
+    let mut pixels = vec![background; (width * height) as usize];
+    for (path, stroke_width) in paths.iter() {
+        draw_path(&mut pixels, width, height, path, *stroke_width, supersample);
+    }
+    to_rgba8(pixels)
+}
+
+/// `render_paths` saved to a png file at `path`
+#[cfg(feature = "image")]
+pub fn save_png(
+    path: &std::path::Path,
+    paths: &[(BezierPath, f32)],
+    width: u32,
+    height: u32,
+    background: Vec4,
+    supersample: u32,
+) -> std::io::Result<()> {
+    let pixels = render_paths(paths, width, height, background, supersample);
+    image::save_buffer(path, &pixels, width, height, image::ColorType::Rgba8)
+        .map_err(std::io::Error::other)
+}
+
+fn draw_path(
+    pixels: &mut [Vec4],
+    width: u32,
+    height: u32,
+    path: &BezierPath,
+    stroke_width: f32,
+    supersample: u32,
+) {
+    let samples = supersample.max(1);
+    let step = 1. / samples as f32;
+    let total = if path.gradient.is_some() {
+        path.length()
+    } else {
+        0.
+    };
+    let mut dist = 0.;
+    for curve in path.curves.iter() {
+        // gradient colors at the ends of the curve, blended by the closest point like in the shader
+        let colors = path.gradient.map(|(start, end)| {
+            let fraction = |dist: f32| if total > 0. { dist / total } else { 0. };
+            let start_color = start.lerp(end, fraction(dist));
+            dist += curve.length();
+            (start_color, start.lerp(end, fraction(dist)))
+        });
+        let (mi, ma) = curve.bounding_box();
+        let (mi, ma) = bounding_box_frame(mi, ma, stroke_width + 1.);
+        let x0 = mi.x.floor().max(0.) as u32;
+        let y0 = mi.y.floor().max(0.) as u32;
+        let x1 = (ma.x.ceil().max(0.) as u32).min(width);
+        let y1 = (ma.y.ceil().max(0.) as u32).min(height);
+        for y in y0..y1 {
+            for x in x0..x1 {
+                let mut coverage = 0.;
+                for sy in 0..samples {
+                    for sx in 0..samples {
+                        let sample = vec2(
+                            x as f32 + (sx as f32 + 0.5) * step,
+                            y as f32 + (sy as f32 + 0.5) * step,
+                        );
+                        let d = curve.distance(sample) - stroke_width;
+                        if d < aa_margin(step) {
+                            coverage += smoothstep(-aa_margin(step), aa_margin(step), -d);
+                        }
+                    }
+                }
+                if coverage <= 0. {
+                    continue;
+                }
+                let color = match colors {
+                    Some((start, end)) => {
+                        let center = vec2(x as f32 + 0.5, y as f32 + 0.5);
+                        start.lerp(end, curve.closest_point(center).0)
+                    }
+                    None => path.color,
+                };
+                let alpha = color.w * coverage / (samples * samples) as f32;
+                let pixel = &mut pixels[(y * width + x) as usize];
+                let out_alpha = alpha + pixel.w * (1. - alpha);
+                if out_alpha <= 0. {
+                    continue;
+                }
+                let rgb = (color.truncate() * alpha + pixel.truncate() * pixel.w * (1. - alpha))
+                    / out_alpha;
+                *pixel = rgb.extend(out_alpha);
+            }
+        }
+    }
+}
+
+fn to_rgba8(pixels: Vec<Vec4>) -> Vec<u8> {
+    let mut buffer = Vec::with_capacity(pixels.len() * 4);
+    for pixel in pixels {
+        let rgba: [f32; 4] = pixel.into();
+        buffer.extend(
+            rgba.iter()
+                .map(|channel| (channel.clamp(0., 1.) * 255.).round() as u8),
+        );
+    }
+    buffer
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn line() -> BezierPath {
+        let mut path = BezierPath::default();
+        path.curves.push(QuadCurve::new(
+            vec2(4., 16.),
+            vec2(16., 16.),
+            vec2(28., 16.),
+        ));
+        path
+    }
+
+    fn alpha(buffer: &[u8], width: u32, x: u32, y: u32) -> u8 {
+        buffer[((y * width + x) * 4 + 3) as usize]
+    }
+
+    #[test]
+    fn on_curve_opaque_far_transparent() {
+        let buffer = render_to_buffer(&line(), 32, 32, 3.);
+        assert_eq!(buffer.len(), 32 * 32 * 4);
+        assert_eq!(alpha(&buffer, 32, 16, 16), 255);
+        assert_eq!(alpha(&buffer, 32, 16, 15), 255);
+        assert_eq!(alpha(&buffer, 32, 16, 2), 0);
+        assert_eq!(alpha(&buffer, 32, 0, 31), 0);
+    }
+
+    #[cfg(feature = "image")]
+    #[test]
+    fn png_has_the_requested_size() {
+        let file = std::env::temp_dir().join(format!("beziers-{}.png", std::process::id()));
+        save_png(&file, &[(line(), 3.)], 40, 24, Vec4::new(1., 1., 1., 1.), 2).unwrap();
+        let dimensions = image::image_dimensions(&file);
+        std::fs::remove_file(&file).unwrap();
+        assert_eq!(dimensions.unwrap(), (40, 24));
+    }
+
+    #[test]
+    fn supersampling_softens_diagonal_edges() {
+        let mut diagonal = BezierPath::default();
+        diagonal
+            .curves
+            .push(QuadCurve::new(vec2(4., 4.), vec2(16., 16.), vec2(28., 28.)));
+        let render = |supersample| {
+            render_paths(&[(diagonal.clone(), 3.)], 32, 32, Vec4::zero(), supersample)
+        };
+        let (single, double) = (render(1), render(2));
+        let partial = |a: u8| a > 0 && a < 255;
+        // pixels the edge only clips at a corner are missed by the single centered sample
+        let softened = (0..32 * 32)
+            .filter(|&i| {
+                let (x, y) = (i % 32, i / 32);
+                !partial(alpha(&single, 32, x, y)) && partial(alpha(&double, 32, x, y))
+            })
+            .count();
+        assert!(softened > 0);
+        // away from the edge nothing changes
+        assert_eq!(alpha(&double, 32, 16, 16), 255);
+        assert_eq!(alpha(&double, 32, 28, 4), 0);
+    }
+}
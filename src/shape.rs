@@ -0,0 +1,653 @@
+use glam::{vec2, Vec2};
+
+use crate::geometry::math::rotate;
+use crate::geometry::*;
+
+#[derive(Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Shape {
+    pub regular: Vec<Vec2>,
+    pub holes: Vec<Vec<Vec2>>,
+}
+
+/// shoelace formula, positive for counterclockwise contours (with y axis up)
+pub fn signed_area(contour: &[Vec2]) -> f32 {
+    let n = contour.len();
+    (0..n)
+        .map(|i| wedge(contour[i], contour[(i + 1) % n]))
+        .sum::<f32>()
+        / 2.
+}
+
+/// how many times `contour` winds around `p`, counterclockwise (with y axis up) turns count
+/// positive. Points exactly on the border may go either way
+fn contour_winding(contour: &[Vec2], p: Vec2) -> i32 {
+    let n = contour.len();
+    let mut winding = 0;
+    for i in 0..n {
+        let (a, b) = (contour[i], contour[(i + 1) % n]);
+        let side = wedge(b - a, p - a);
+        if a.y <= p.y && b.y > p.y && side > 0. {
+            winding += 1;
+        } else if a.y > p.y && b.y <= p.y && side < 0. {
+            winding -= 1;
+        }
+    }
+    winding
+}
+
+/// drops repeated points and points closer than `tolerance` to the outline without them.
+/// Contours that would fall under 3 points only lose the repeats
+fn simplify_contour(contour: &mut Vec<Vec2>, tolerance: f32) {
+    contour.dedup();
+    while contour.len() > 1 && contour.first() == contour.last() {
+        contour.pop();
+    }
+    if contour.len() <= 3 {
+        return;
+    }
+    // the point farthest from the first one is a corner of the convex hull, which stays at
+    // any tolerance. Ramer-douglas-peucker keeps its first point so the ring is started there
+    let origin = contour[0];
+    let corner = (0..contour.len())
+        .max_by(|&a, &b| {
+            let dist = |i: usize| (contour[i] - origin).length_squared();
+            dist(a).partial_cmp(&dist(b)).unwrap()
+        })
+        .unwrap();
+    contour.rotate_left(corner);
+    contour.push(contour[0]);
+    let mut simplified = vec![];
+    douglas_peucker(contour, tolerance, &mut simplified);
+    simplified.pop();
+    contour.pop();
+    if simplified.len() >= 3 {
+        *contour = simplified;
+    }
+}
+
+/// which points a set of possibly nested or overlapping contours covers
+#[derive(Clone, Copy, Debug, PartialEq, Default)]
+pub enum FillRule {
+    /// inside when a ray from the point crosses the contours an odd number of times,
+    /// holes are cut out whatever their orientation
+    #[default]
+    EvenOdd,
+    /// inside when the contours wind around the point at all, holes need the opposite
+    /// orientation of the contour around them
+    NonZero,
+}
+
+/// `p` inside or on the border of counterclockwise triangle `a`, `b`, `c`
+fn in_triangle(p: Vec2, a: Vec2, b: Vec2, c: Vec2) -> bool {
+    wedge(b - a, p - a) >= 0. && wedge(c - b, p - b) >= 0. && wedge(a - c, p - c) >= 0.
+}
+
+impl Shape {
+    pub fn from_regular(regular: Vec<Vec2>) -> Shape {
+        Shape {
+            regular,
+            holes: Vec::new(),
+        }
+    }
+
+    /// area of the outer contour minus areas of the holes, independent of winding
+    pub fn area(&self) -> f32 {
+        signed_area(&self.regular).abs()
+            - self
+                .holes
+                .iter()
+                .map(|hole| signed_area(hole).abs())
+                .sum::<f32>()
+    }
+
+    /// reverses contours as needed so `regular` is counterclockwise (with y axis up) and the
+    /// holes clockwise, the orientation `FillRule::NonZero` expects
+    pub fn normalize_winding(&mut self) {
+        if signed_area(&self.regular) < 0. {
+            self.regular.reverse();
+        }
+        for hole in self.holes.iter_mut() {
+            if signed_area(hole) > 0. {
+                hole.reverse();
+            }
+        }
+    }
+
+    /// the outer contour has at least 3 points and every point of every hole is inside it.
+    /// Holes overlapping each other aren't detected
+    pub fn is_valid(&self) -> bool {
+        self.regular.len() >= 3
+            && self
+                .holes
+                .iter()
+                .flatten()
+                .all(|&p| contour_winding(&self.regular, p) != 0)
+    }
+
+    /// removes repeated points and points whose removal moves the outline by less than
+    /// `tolerance`, from the outer contour and every hole
+    pub fn simplify(&mut self, tolerance: f32) {
+        simplify_contour(&mut self.regular, tolerance);
+        for hole in self.holes.iter_mut() {
+            simplify_contour(hole, tolerance);
+        }
+    }
+
+    /// sum of windings of the outer contour and the holes around `p`
+    pub fn winding_number(&self, p: Vec2) -> i32 {
+        contour_winding(&self.regular, p)
+            + self
+                .holes
+                .iter()
+                .map(|hole| contour_winding(hole, p))
+                .sum::<i32>()
+    }
+
+    /// `p` is filled under `rule`. With `FillRule::EvenOdd` and holes that don't overlap
+    /// that's inside the outer contour and outside of every hole
+    pub fn contains(&self, p: Vec2, rule: FillRule) -> bool {
+        let winding = self.winding_number(p);
+        match rule {
+            FillRule::EvenOdd => winding % 2 != 0,
+            FillRule::NonZero => winding != 0,
+        }
+    }
+
+    /// fill triangles by ear clipping. Holes are joined to the outer contour by bridge
+    /// edges first, so the result is a single polygon. Returns `regular` followed by all
+    /// holes as vertices and indices of counterclockwise (with y axis up) triangles
+    pub fn triangulate(&self) -> (Vec<Vec2>, Vec<u32>) {
+        let mut vertices = self.regular.clone();
+        let mut ring = contour_indices(&self.regular, 0, true);
+        let mut holes = vec![];
+        for hole in self.holes.iter() {
+            if hole.len() >= 3 {
+                holes.push(contour_indices(hole, vertices.len() as u32, false));
+            }
+            vertices.extend(hole.iter());
+        }
+        // rightmost holes go first so bridges of later holes can't cross them
+        let max_x = |hole: &Vec<u32>| {
+            hole.iter()
+                .map(|&i| vertices[i as usize].x)
+                .fold(f32::MIN, f32::max)
+        };
+        holes.sort_by(|a, b| max_x(b).partial_cmp(&max_x(a)).unwrap());
+        for hole in holes {
+            bridge_hole(&vertices, &mut ring, &hole);
+        }
+        (vertices.clone(), clip_ears(&vertices, ring))
+    }
+}
+
+/// indices of `contour` offset by `offset` in counterclockwise order if `ccw`, clockwise otherwise
+fn contour_indices(contour: &[Vec2], offset: u32, ccw: bool) -> Vec<u32> {
+    let mut indices: Vec<u32> = (offset..offset + contour.len() as u32).collect();
+    if (signed_area(contour) > 0.) != ccw {
+        indices.reverse();
+    }
+    indices
+}
+
+/// splices clockwise `hole` into counterclockwise `ring` through a bridge from the
+/// rightmost hole vertex to a visible ring vertex (Eberly, "Triangulation by Ear Clipping")
+fn bridge_hole(vertices: &[Vec2], ring: &mut Vec<u32>, hole: &[u32]) {
+    let point = |i: u32| vertices[i as usize];
+    let start = (0..hole.len())
+        .max_by(|&a, &b| point(hole[a]).x.partial_cmp(&point(hole[b]).x).unwrap())
+        .unwrap();
+    let m = point(hole[start]);
+    // closest ring edge hit by the ray from m to +x, and its endpoint with larger x
+    let mut hit: Option<(f32, usize)> = None;
+    for k in 0..ring.len() {
+        let (p, q) = (point(ring[k]), point(ring[(k + 1) % ring.len()]));
+        if (p.y > m.y) == (q.y > m.y) {
+            continue;
+        }
+        let x = p.x + (m.y - p.y) * (q.x - p.x) / (q.y - p.y);
+        if x >= m.x && hit.is_none_or(|(best, _)| x < best) {
+            let candidate = if p.x > q.x { k } else { (k + 1) % ring.len() };
+            hit = Some((x, candidate));
+        }
+    }
+    let (x, mut bridge) = match hit {
+        Some(hit) => hit,
+        None => return,
+    };
+    // a vertex inside the triangle m, hit, candidate may hide the candidate,
+    // the one with the smallest angle to the ray is visible
+    let i = vec2(x, m.y);
+    let p = point(ring[bridge]);
+    let (a, b) = if p.y > m.y { (i, p) } else { (p, i) };
+    let mut best_tan = f32::MAX;
+    for (k, &v) in ring.iter().enumerate() {
+        let v = point(v);
+        if k == bridge || v == p || v.x <= m.x || !in_triangle(v, m, a, b) {
+            continue;
+        }
+        let tan = (v.y - m.y).abs() / (v.x - m.x);
+        if tan < best_tan {
+            best_tan = tan;
+            bridge = k;
+        }
+    }
+    // earlier bridges duplicate vertices, pick the copy whose corner faces m
+    let n = ring.len();
+    let target = point(ring[bridge]);
+    bridge = (0..n)
+        .filter(|&k| point(ring[k]) == target)
+        .find(|&k| {
+            let (a, b, c) = (
+                point(ring[(k + n - 1) % n]),
+                target,
+                point(ring[(k + 1) % n]),
+            );
+            let (left_in, left_out) = (wedge(b - a, m - a) > 0., wedge(c - b, m - b) > 0.);
+            if wedge(b - a, c - b) >= 0. {
+                left_in && left_out
+            } else {
+                left_in || left_out
+            }
+        })
+        .unwrap_or(bridge);
+    let mut spliced = Vec::with_capacity(ring.len() + hole.len() + 2);
+    spliced.extend_from_slice(&ring[..=bridge]);
+    spliced.extend(hole[start..].iter().chain(hole[..start].iter()));
+    spliced.push(hole[start]);
+    spliced.extend_from_slice(&ring[bridge..]);
+    *ring = spliced;
+}
+
+/// triangulates counterclockwise simple polygon `ring`
+fn clip_ears(vertices: &[Vec2], mut ring: Vec<u32>) -> Vec<u32> {
+    let point = |i: u32| vertices[i as usize];
+    let mut indices = vec![];
+    while ring.len() > 3 {
+        let n = ring.len();
+        let is_ear = |i: usize| {
+            let (a, b, c) = (
+                point(ring[(i + n - 1) % n]),
+                point(ring[i]),
+                point(ring[(i + 1) % n]),
+            );
+            wedge(b - a, c - b) > 0.
+                && ring.iter().all(|&v| {
+                    let v = point(v);
+                    v == a || v == b || v == c || !in_triangle(v, a, b, c)
+                })
+        };
+        if let Some(i) = (0..n).find(|&i| is_ear(i)) {
+            indices.extend_from_slice(&[ring[(i + n - 1) % n], ring[i], ring[(i + 1) % n]]);
+            ring.remove(i);
+            continue;
+        }
+        // no ears left means collinear or self intersecting leftovers,
+        // drop a flat vertex if there is one, otherwise give up on the rest
+        let flat = (0..n).find(|&i| {
+            let (a, b, c) = (
+                point(ring[(i + n - 1) % n]),
+                point(ring[i]),
+                point(ring[(i + 1) % n]),
+            );
+            wedge(b - a, c - b).abs() <= f32::EPSILON
+        });
+        match flat {
+            Some(i) => {
+                ring.remove(i);
+            }
+            None => return indices,
+        }
+    }
+    if ring.len() == 3 {
+        indices.extend_from_slice(&ring);
+    }
+    indices
+}
+
+/// appends points of the arc around `center` from `from` turning by `sweep` radians,
+/// counterclockwise for positive ones (with y axis up). Chords stay within `tolerance` of
+/// the circle, neither end of the arc is added
+fn push_arc(points: &mut Vec<Vec2>, center: Vec2, from: Vec2, sweep: f32, tolerance: f32) {
+    let radius = (from - center).length();
+    if radius <= tolerance {
+        return;
+    }
+    // sagitta of a chord spanning `step` is radius * (1 - cos(step / 2))
+    let step = 2. * (1. - tolerance / radius).acos();
+    let num = (sweep.abs() / step).ceil() as usize;
+    for i in 1..num {
+        points.push(center + rotate(from - center, sweep * i as f32 / num as f32));
+    }
+}
+
+/// angle turning `from` to `to` the short way, positive counterclockwise (with y axis up)
+fn turn(from: Vec2, to: Vec2) -> f32 {
+    wedge(from, to).atan2(from.dot(to))
+}
+
+/// appends `points` skipping ones repeating the last point of `contour`
+fn push_points(contour: &mut Vec<Vec2>, points: impl IntoIterator<Item = Vec2>) {
+    for point in points {
+        if contour.last() != Some(&point) {
+            contour.push(point);
+        }
+    }
+}
+
+/// drops the last point of a contour when it repeats the first, contours are implicitly closed
+fn unclose(contour: &mut Vec<Vec2>) {
+    if contour.len() > 1 && contour.first() == contour.last() {
+        contour.pop();
+    }
+}
+
+/// `cap` around the end of a path at `center` leaving in `direction`, from the last
+/// point of `contour` to `to` on the other side
+fn push_cap(
+    contour: &mut Vec<Vec2>,
+    cap: CapStyle,
+    center: Vec2,
+    direction: Vec2,
+    to: Vec2,
+    width: f32,
+    tolerance: f32,
+) {
+    if direction.length_squared() <= f32::EPSILON {
+        return;
+    }
+    let from = contour[contour.len() - 1];
+    match cap {
+        CapStyle::Butt => {}
+        CapStyle::Square => {
+            let extent = direction.normalize() * width;
+            push_points(contour, vec![from + extent, to + extent]);
+        }
+        CapStyle::Round => {
+            let sweep = std::f32::consts::PI.copysign(wedge(from - center, direction));
+            push_arc(contour, center, from, sweep, tolerance);
+        }
+    }
+}
+
+impl BezierPath {
+    /// outline of a stroke of `width` along the path, for exporters that can only fill.
+    /// Both sides are the curves `offset` by `width` within `tolerance`, corners get round
+    /// joins and the ends of open paths get `cap`, pass `self.cap` for the outline of what
+    /// `vertices(width)` draws. An open path gives a single contour, a closed one gives
+    /// the outer side with the inner one as a hole. Sides fold over themselves inside turns
+    /// tighter than `width`, fill the shape with `FillRule::NonZero`
+    pub fn outline(&self, width: f32, tolerance: f32, cap: CapStyle) -> Shape {
+        let (first, last) = match (self.curves.first(), self.curves.last()) {
+            (Some(first), Some(last)) => (*first, *last),
+            _ => return Shape::default(),
+        };
+        let mut left = self.stroke_side(width, tolerance);
+        let mut right = self.stroke_side(-width, tolerance);
+        if self.closed {
+            unclose(&mut left);
+            unclose(&mut right);
+            if signed_area(&left).abs() < signed_area(&right).abs() {
+                std::mem::swap(&mut left, &mut right);
+            }
+            // both sides follow the path, the hole has to wind the other way
+            right.reverse();
+            return Shape {
+                regular: left,
+                holes: vec![right],
+            };
+        }
+        right.reverse();
+        let mut contour = left;
+        push_cap(
+            &mut contour,
+            cap,
+            last.c,
+            last.end_direction(),
+            right[0],
+            width,
+            tolerance,
+        );
+        push_points(&mut contour, right);
+        let start = contour[0];
+        push_cap(
+            &mut contour,
+            cap,
+            first.a,
+            -first.start_direction(),
+            start,
+            width,
+            tolerance,
+        );
+        unclose(&mut contour);
+        Shape::from_regular(contour)
+    }
+
+    /// one side of the stroke, `distance` to the left (with y axis up) of the curves.
+    /// Offsets of consecutive curves are connected by an arc around the point they share
+    fn stroke_side(&self, distance: f32, tolerance: f32) -> Vec<Vec2> {
+        let mut side: Vec<Vec2> = vec![];
+        let arc = |side: &mut Vec<Vec2>, center: Vec2, to: Vec2| {
+            let from = side[side.len() - 1];
+            push_arc(
+                side,
+                center,
+                from,
+                turn(from - center, to - center),
+                tolerance,
+            );
+        };
+        for curve in self.curves.iter() {
+            let offset = curve.offset(distance, tolerance);
+            if !side.is_empty() {
+                arc(&mut side, curve.a, offset[0]);
+            }
+            push_points(&mut side, offset);
+        }
+        if self.closed {
+            let start = side[0];
+            arc(&mut side, self.curves[0].a, start);
+        }
+        side
+    }
+
+    /// outline polygon of the path flattened with `tolerance`, the path is treated as
+    /// closed. Contours of a `Shape` are implicitly closed so the end point coinciding
+    /// with the start isn't repeated
+    pub fn to_shape(&self, tolerance: f32) -> Shape {
+        let mut regular: Vec<Vec2> = vec![];
+        for curve in self.curves.iter() {
+            for point in curve.flatten(tolerance) {
+                if regular.last() != Some(&point) {
+                    regular.push(point);
+                }
+            }
+        }
+        if regular.len() > 1 && regular.first() == regular.last() {
+            regular.pop();
+        }
+        Shape::from_regular(regular)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn square(center: Vec2, half: f32) -> Vec<Vec2> {
+        vec![
+            center + vec2(-half, -half),
+            center + vec2(half, -half),
+            center + vec2(half, half),
+            center + vec2(-half, half),
+        ]
+    }
+
+    fn square_with_hole() -> Shape {
+        Shape {
+            regular: square(vec2(5., 5.), 5.),
+            holes: vec![square(vec2(5., 5.), 2.)],
+        }
+    }
+
+    #[test]
+    fn triangulate_square_with_hole() {
+        let shape = square_with_hole();
+        let (vertices, indices) = shape.triangulate();
+        assert_eq!(vertices.len(), 8);
+        // 8 vertices of a polygon with one hole, n + 2 * holes - 2 triangles
+        assert_eq!(indices.len(), 8 * 3);
+        assert!(indices.iter().all(|&i| (i as usize) < vertices.len()));
+        let mut area = 0.;
+        for triangle in indices.chunks(3) {
+            let [a, b, c] = [0, 1, 2].map(|k| vertices[triangle[k] as usize]);
+            let triangle_area = wedge(b - a, c - a) / 2.;
+            assert!(triangle_area > 0., "clockwise triangle {:?}", triangle);
+            area += triangle_area;
+            let centroid = (a + b + c) / 3.;
+            assert!(
+                shape.contains(centroid, FillRule::EvenOdd),
+                "{:?}",
+                triangle
+            );
+        }
+        assert!((area - 84.).abs() < 1e-3);
+    }
+
+    #[test]
+    fn area_and_contains_with_hole() {
+        let shape = square_with_hole();
+        assert!((shape.area() - 84.).abs() < 1e-4);
+        let mut reversed = square_with_hole();
+        reversed.regular.reverse();
+        assert!((reversed.area() - 84.).abs() < 1e-4);
+        for rule in [FillRule::EvenOdd, FillRule::NonZero] {
+            let mut shape = square_with_hole();
+            shape.normalize_winding();
+            assert!(shape.contains(vec2(1., 1.), rule));
+            assert!(!shape.contains(vec2(5., 5.), rule), "inside the hole");
+            assert!(!shape.contains(vec2(12., 5.), rule), "outside");
+        }
+    }
+
+    #[test]
+    fn to_shape_points_follow_curvature() {
+        let lens = |bulge: f32| {
+            BezierPath::from_svg(&format!("M 0 0 Q 50 {} 100 0 Q 50 {} 0 0", bulge, -bulge))
+                .unwrap()
+        };
+        let flat = lens(1.).to_shape(0.1);
+        let curved = lens(80.).to_shape(0.1);
+        assert!(curved.regular.len() > flat.regular.len());
+        // the end meets the start, the closing point isn't repeated
+        for shape in [flat, curved] {
+            assert_eq!(shape.regular[0], vec2(0., 0.));
+            assert_ne!(shape.regular.last(), shape.regular.first());
+            assert!(shape.regular.contains(&vec2(100., 0.)));
+        }
+    }
+
+    #[test]
+    fn nested_rings_under_both_rules() {
+        let center = vec2(0., 0.);
+        // a hole inside the outer square with a solid island inside the hole
+        let mut shape = Shape {
+            regular: square(center, 10.),
+            holes: vec![square(center, 6.), square(center, 2.)],
+        };
+        let (outer, ring, island, outside) =
+            (vec2(8., 0.), vec2(4., 0.), vec2(0., 0.), vec2(12., 0.));
+        // all counterclockwise, as svg files often have them
+        assert_eq!(shape.winding_number(outer), 1);
+        assert_eq!(shape.winding_number(ring), 2);
+        assert_eq!(shape.winding_number(island), 3);
+        assert_eq!(shape.winding_number(outside), 0);
+        assert!(shape.contains(outer, FillRule::EvenOdd));
+        assert!(!shape.contains(ring, FillRule::EvenOdd));
+        assert!(shape.contains(island, FillRule::EvenOdd));
+        assert!(shape.contains(ring, FillRule::NonZero));
+        assert!(!shape.contains(outside, FillRule::NonZero));
+        // the island winds back against the hole, non-zero cuts the ring out as well
+        shape.holes[0].reverse();
+        assert_eq!(shape.winding_number(ring), 0);
+        assert_eq!(shape.winding_number(island), 1);
+        for rule in [FillRule::EvenOdd, FillRule::NonZero] {
+            assert!(shape.contains(outer, rule));
+            assert!(!shape.contains(ring, rule));
+            assert!(shape.contains(island, rule));
+            assert!(!shape.contains(outside, rule));
+        }
+    }
+
+    #[test]
+    fn simplify_drops_edge_midpoints() {
+        // starting at a midpoint, which isn't a corner to keep
+        let mut shape = Shape::from_regular(vec![
+            vec2(5., 0.),
+            vec2(10., 0.),
+            vec2(10., 5.),
+            vec2(10., 10.),
+            vec2(5., 10.),
+            vec2(0., 10.),
+            vec2(0., 5.),
+            vec2(0., 0.),
+            vec2(5., 0.),
+        ]);
+        shape.simplify(0.1);
+        assert_eq!(shape.regular.len(), 4);
+        for corner in square(vec2(5., 5.), 5.) {
+            assert!(shape.regular.contains(&corner), "{:?}", corner);
+        }
+        assert!((shape.area() - 100.).abs() < 1e-4);
+    }
+
+    #[test]
+    fn mis_wound_hole_is_flipped() {
+        let mut shape = square_with_hole();
+        // both contours counterclockwise, the hole winds the same way as the outline
+        assert!(signed_area(&shape.regular) > 0. && signed_area(&shape.holes[0]) > 0.);
+        assert_eq!(shape.winding_number(vec2(5., 5.)), 2);
+        shape.normalize_winding();
+        assert!(signed_area(&shape.regular) > 0.);
+        assert!(signed_area(&shape.holes[0]) < 0.);
+        assert_eq!(shape.winding_number(vec2(5., 5.)), 0);
+        // a clockwise outline is turned around too
+        let mut reversed = square_with_hole();
+        reversed.regular.reverse();
+        reversed.normalize_winding();
+        assert!(signed_area(&reversed.regular) > 0.);
+        assert!(shape.is_valid());
+        shape.holes.push(square(vec2(20., 20.), 1.));
+        assert!(!shape.is_valid(), "hole outside the outline");
+    }
+
+    #[test]
+    fn outline_of_a_straight_curve_is_a_rectangle() {
+        let mut path = BezierPath::default();
+        path.curves
+            .push(QuadCurve::new(vec2(0., 0.), vec2(50., 0.), vec2(100., 0.)));
+        let shape = path.outline(5., 0.1, CapStyle::Butt);
+        assert!(shape.holes.is_empty());
+        assert_eq!(shape.regular.len(), 4, "{:?}", shape.regular);
+        for &corner in &[vec2(0., 5.), vec2(100., 5.), vec2(100., -5.), vec2(0., -5.)] {
+            let nearest = shape
+                .regular
+                .iter()
+                .map(|&p| (p - corner).length())
+                .fold(f32::INFINITY, f32::min);
+            assert!(
+                nearest < 1e-4,
+                "{:?} missing from {:?}",
+                corner,
+                shape.regular
+            );
+        }
+        assert!((shape.area() - 1000.).abs() < 1e-2);
+        // other caps reach past the ends
+        let square = path.outline(5., 0.1, CapStyle::Square);
+        assert!((square.area() - 1100.).abs() < 1e-2);
+        let round = path.outline(5., 0.1, path.cap);
+        assert!(round.regular.len() > 4);
+        assert!(round.area() > 1000. && round.area() < 1100.);
+    }
+}
@@ -0,0 +1,263 @@
+use glam::{vec2, Vec2};
+use std::fmt;
+
+use crate::geometry::*;
+
+/// error of svg path data parsing, offsets are byte positions in the input
+#[derive(Debug, Clone, PartialEq)]
+pub enum ParseError {
+    /// path data has to start with a moveto
+    MissingMoveTo,
+    /// only M, L, Q and Z (and their relative forms) map to quadratic curves
+    UnsupportedCommand(usize, char),
+    UnexpectedChar(usize, char),
+    /// command at offset expects more coordinates than given
+    MissingArguments(usize, char),
+    /// `BezierPath` is a single contour, a second moveto can't be represented
+    MultipleSubpaths(usize),
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ParseError::MissingMoveTo => write!(f, "path data must start with a moveto"),
+            ParseError::UnsupportedCommand(at, cmd) => {
+                write!(f, "unsupported command '{}' at {}", cmd, at)
+            }
+            ParseError::UnexpectedChar(at, ch) => write!(f, "unexpected '{}' at {}", ch, at),
+            ParseError::MissingArguments(at, cmd) => {
+                write!(f, "missing arguments for '{}' at {}", cmd, at)
+            }
+            ParseError::MultipleSubpaths(at) => write!(f, "second subpath at {}", at),
+        }
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+struct Tokens<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Tokens<'a> {
+    fn skip_separators(&mut self) {
+        while self.pos < self.data.len()
+            && (self.data[self.pos].is_ascii_whitespace() || self.data[self.pos] == b',')
+        {
+            self.pos += 1;
+        }
+    }
+
+    fn peek(&mut self) -> Option<u8> {
+        self.skip_separators();
+        self.data.get(self.pos).copied()
+    }
+
+    fn skip_digits(&mut self) -> usize {
+        let start = self.pos;
+        while self.pos < self.data.len() && self.data[self.pos].is_ascii_digit() {
+            self.pos += 1;
+        }
+        self.pos - start
+    }
+
+    /// `[+-]?(digits[.digits]|.digits)([eE][+-]?digits)?`
+    fn number(&mut self) -> Option<f32> {
+        self.skip_separators();
+        let start = self.pos;
+        if let Some(b'+') | Some(b'-') = self.data.get(self.pos) {
+            self.pos += 1;
+        }
+        let mut digits = self.skip_digits();
+        if self.data.get(self.pos) == Some(&b'.') {
+            self.pos += 1;
+            digits += self.skip_digits();
+        }
+        if digits == 0 {
+            self.pos = start;
+            return None;
+        }
+        if let Some(b'e') | Some(b'E') = self.data.get(self.pos) {
+            let mantissa_end = self.pos;
+            self.pos += 1;
+            if let Some(b'+') | Some(b'-') = self.data.get(self.pos) {
+                self.pos += 1;
+            }
+            if self.skip_digits() == 0 {
+                self.pos = mantissa_end;
+            }
+        }
+        std::str::from_utf8(&self.data[start..self.pos])
+            .ok()
+            .and_then(|number| number.parse().ok())
+    }
+
+    fn point(&mut self, at: usize, cmd: char) -> Result<Vec2, ParseError> {
+        let x = self.number();
+        let y = self.number();
+        match (x, y) {
+            (Some(x), Some(y)) => Ok(vec2(x, y)),
+            _ => Err(ParseError::MissingArguments(at, cmd)),
+        }
+    }
+}
+
+impl BezierPath {
+    /// builds a path from svg path data with M, L, Q, Z commands in absolute
+    /// and relative forms. Lines become quadratic curves with control point in the middle
+    pub fn from_svg(data: &str) -> Result<BezierPath, ParseError> {
+        let mut tokens = Tokens {
+            data: data.as_bytes(),
+            pos: 0,
+        };
+        let mut path = BezierPath::default();
+        let mut start: Option<Vec2> = None;
+        let mut current = Vec2::zero();
+        let mut command: Option<char> = None;
+        while let Some(next) = tokens.peek() {
+            let at = tokens.pos;
+            let cmd = if next.is_ascii_alphabetic() {
+                tokens.pos += 1;
+                next as char
+            } else if next.is_ascii_digit() || b"+-.".contains(&next) {
+                // numbers after a command repeat it, after a moveto they are lineto
+                match command {
+                    Some('M') => 'L',
+                    Some('m') => 'l',
+                    Some('Z') | Some('z') | None => {
+                        return Err(ParseError::UnexpectedChar(at, next as char))
+                    }
+                    Some(cmd) => cmd,
+                }
+            } else {
+                return Err(ParseError::UnexpectedChar(at, next as char));
+            };
+            if start.is_none() && cmd != 'M' && cmd != 'm' {
+                return Err(ParseError::MissingMoveTo);
+            }
+            if path.closed && cmd != 'Z' && cmd != 'z' {
+                return Err(ParseError::MultipleSubpaths(at));
+            }
+            let relative = if cmd.is_ascii_lowercase() {
+                current
+            } else {
+                Vec2::zero()
+            };
+            match cmd {
+                'M' | 'm' => {
+                    if start.is_some() {
+                        return Err(ParseError::MultipleSubpaths(at));
+                    }
+                    current = relative + tokens.point(at, cmd)?;
+                    start = Some(current);
+                }
+                'L' | 'l' => {
+                    let point = relative + tokens.point(at, cmd)?;
+                    path.curves
+                        .push(QuadCurve::new(current, (current + point) / 2., point));
+                    current = point;
+                }
+                'Q' | 'q' => {
+                    let control = relative + tokens.point(at, cmd)?;
+                    let point = relative + tokens.point(at, cmd)?;
+                    path.curves.push(QuadCurve::new(current, control, point));
+                    current = point;
+                }
+                'Z' | 'z' => {
+                    if let Some(start) = start {
+                        if current != start {
+                            path.curves.push(QuadCurve::new(
+                                current,
+                                (current + start) / 2.,
+                                start,
+                            ));
+                        }
+                        current = start;
+                    }
+                    path.closed = true;
+                }
+                _ => return Err(ParseError::UnsupportedCommand(at, cmd)),
+            }
+            command = Some(cmd);
+        }
+        path.last = start.map(|_| current);
+        Ok(path)
+    }
+
+    /// svg path data of the curves, `M` followed by a `Q` per curve and `Z` for closed paths.
+    /// Curves are expected to be connected, only the first curve's start is emitted
+    pub fn to_svg(&self) -> String {
+        let mut data = String::new();
+        let start = self.curves.first().map(|curve| curve.a).or(self.last);
+        if let Some(start) = start {
+            data.push_str(&format!("M {} {}", start.x, start.y));
+        }
+        for curve in self.curves.iter() {
+            data.push_str(&format!(
+                " Q {} {} {} {}",
+                curve.control.x, curve.control.y, curve.c.x, curve.c.y
+            ));
+        }
+        if self.closed {
+            data.push_str(" Z");
+        }
+        data
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn multi_segment_path() {
+        let path = BezierPath::from_svg("M 0 0 Q 5,10 10,0 l 10 0 q 5 -10 10 0 Z").unwrap();
+        assert_eq!(
+            path.curves,
+            vec![
+                QuadCurve::new(vec2(0., 0.), vec2(5., 10.), vec2(10., 0.)),
+                QuadCurve::new(vec2(10., 0.), vec2(15., 0.), vec2(20., 0.)),
+                QuadCurve::new(vec2(20., 0.), vec2(25., -10.), vec2(30., 0.)),
+                QuadCurve::new(vec2(30., 0.), vec2(15., 0.), vec2(0., 0.)),
+            ]
+        );
+        assert!(path.closed);
+        assert_eq!(path.last, Some(vec2(0., 0.)));
+    }
+
+    #[test]
+    fn malformed_input() {
+        assert_eq!(
+            BezierPath::from_svg("L 1 1").unwrap_err(),
+            ParseError::MissingMoveTo
+        );
+        assert_eq!(
+            BezierPath::from_svg("M 0 0 C 1 1 2 2 3 3").unwrap_err(),
+            ParseError::UnsupportedCommand(6, 'C')
+        );
+        assert_eq!(
+            BezierPath::from_svg("M 0 0 Q 1 1").unwrap_err(),
+            ParseError::MissingArguments(6, 'Q')
+        );
+        assert_eq!(
+            BezierPath::from_svg("M 0 0 L 1 # 2").unwrap_err(),
+            ParseError::MissingArguments(6, 'L')
+        );
+        assert_eq!(
+            BezierPath::from_svg("M 0 0 L 1 1 M 2 2").unwrap_err(),
+            ParseError::MultipleSubpaths(12)
+        );
+    }
+
+    #[test]
+    fn round_trip() {
+        let data = "M 0 0 Q 5 10 10 0 Q 15 -10.5 20 0.25 Q 10 -20 0 0 Z";
+        let path = BezierPath::from_svg(data).unwrap();
+        assert_eq!(path.to_svg(), data);
+        let parsed = BezierPath::from_svg(&path.to_svg()).unwrap();
+        assert_eq!(parsed.curves, path.curves);
+        assert_eq!(parsed.closed, path.closed);
+        assert_eq!(BezierPath::default().to_svg(), "");
+    }
+}